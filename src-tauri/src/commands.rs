@@ -1,14 +1,23 @@
-use tauri::State;
+use std::path::PathBuf;
+
+use tauri::{AppHandle, State};
 use uuid::Uuid;
 
 use crate::{
+    discovery,
     error::{AppError, AppResult},
+    export, health,
     models::{
-        ActivityStats, AppSettings, AppSettingsUpdate, Project, ProjectInput, WorkspaceInput,
-        WorkspaceRecord,
+        ActivityStats, AppSettings, AppSettingsUpdate, DiscoveredTask, LaunchSession, PresetUsage,
+        ProcessInstance, ProcessOutput, ProcessStopResult, Project, ProjectFilter, ProjectImport,
+        ProjectInput, ProjectNote, ProjectNoteInput, ProjectPathHealth, ProjectSearchResult,
+        ProjectSort, ProjectSuggestion, RelocationCandidate, ScanCandidate, SessionSummary,
+        Suggestion, Tag, TagInput, TagSummary, WatchedFolderInput, WorkspaceCheck, WorkspaceExport,
+        WorkspaceInput, WorkspaceRecord, WorkspaceRepair, WorkspaceStatus,
     },
-    project,
-    state::AppState,
+    notes, palette, process, project, scan, search, session,
+    state::{AppState, LaunchRequest},
+    tags, watch,
 };
 
 #[tauri::command]
@@ -53,13 +62,108 @@ pub async fn delete_workspace(state: State<'_, AppState>, workspace_id: Uuid) ->
     state.delete_workspace(workspace_id).await
 }
 
+#[tauri::command]
+pub async fn get_workspace_statuses(state: State<'_, AppState>) -> AppResult<Vec<WorkspaceStatus>> {
+    Ok(state.workspace_statuses().await)
+}
+
+#[tauri::command]
+pub async fn reopen_workspace(
+    state: State<'_, AppState>,
+    workspace_id: Uuid,
+) -> AppResult<WorkspaceStatus> {
+    state.reopen_workspace(workspace_id).await
+}
+
+#[tauri::command]
+pub async fn relocate_workspace(
+    state: State<'_, AppState>,
+    workspace_id: Uuid,
+    database_path: PathBuf,
+) -> AppResult<WorkspaceRecord> {
+    state.relocate_workspace(workspace_id, database_path).await
+}
+
+#[tauri::command]
+pub async fn check_workspace(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+) -> AppResult<WorkspaceCheck> {
+    state.check_workspace(workspace_id).await
+}
+
+#[tauri::command]
+pub async fn repair_workspace(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+) -> AppResult<WorkspaceRepair> {
+    state.repair_workspace(workspace_id).await
+}
+
+#[tauri::command]
+pub async fn recreate_workspace_database(
+    state: State<'_, AppState>,
+    workspace_id: Uuid,
+) -> AppResult<WorkspaceStatus> {
+    state.recreate_workspace_database(workspace_id).await
+}
+
 #[tauri::command]
 pub async fn list_projects(
     state: State<'_, AppState>,
     workspace_id: Option<Uuid>,
+    filter: Option<ProjectFilter>,
+    sort: Option<ProjectSort>,
 ) -> Result<Vec<Project>, AppError> {
     let handle = state.workspace_handle(workspace_id).await?;
-    project::list_projects(&handle).await
+    let mut projects = project::list_projects(&handle).await?;
+    if let Some(filter) = filter {
+        projects.retain(|project| filter.matches(project));
+    }
+    if sort == Some(ProjectSort::Frecency) {
+        // Stable, so equally ranked projects stay in name order
+        projects.sort_by_key(|project| (!project.pinned, std::cmp::Reverse(project.frecency)));
+    }
+    state.attach_git_status(&mut projects, false).await;
+    Ok(projects)
+}
+
+#[tauri::command]
+pub async fn search_projects(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    query: String,
+    limit: Option<usize>,
+) -> AppResult<Vec<ProjectSearchResult>> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    search::search_projects(&handle, &query, limit).await
+}
+
+#[tauri::command]
+pub async fn get_suggestions(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    query: String,
+    limit: Option<usize>,
+) -> AppResult<Vec<Suggestion>> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    let settings = state.get_settings().await;
+    palette::get_suggestions(&handle, &settings.launch_presets, &query, limit).await
+}
+
+#[tauri::command]
+pub async fn refresh_git_status(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    project_id: Option<Uuid>,
+) -> AppResult<Vec<Project>> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    let mut projects = match project_id {
+        Some(project_id) => vec![project::get_project(&handle, project_id).await?],
+        None => project::list_projects(&handle).await?,
+    };
+    state.attach_git_status(&mut projects, true).await;
+    Ok(projects)
 }
 
 #[tauri::command]
@@ -69,7 +173,7 @@ pub async fn upsert_project(
     payload: ProjectInput,
 ) -> Result<Project, AppError> {
     let handle = state.workspace_handle(workspace_id).await?;
-    project::upsert_project(&handle, payload).await
+    state.upsert_project(&handle, payload).await
 }
 
 #[tauri::command]
@@ -83,80 +187,447 @@ pub async fn delete_project(
 }
 
 #[tauri::command]
-pub async fn launch_project(
+pub async fn list_trash(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+) -> AppResult<Vec<Project>> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    let retention_days = state.get_settings().await.trash_retention_days;
+    project::purge_expired(&handle.pool, retention_days).await?;
+    project::list_trash(&handle).await
+}
+
+#[tauri::command]
+pub async fn restore_project(
     state: State<'_, AppState>,
     workspace_id: Option<Uuid>,
     project_id: Uuid,
-) -> AppResult<()> {
+) -> AppResult<Project> {
     let handle = state.workspace_handle(workspace_id).await?;
-    let project = project::get_project(&handle, project_id).await?;
-    
-    // Check if already running
-    {
-        let mut inner = state.inner.write().await;
-        if let Some(child) = inner.running_processes.get_mut(&project_id) {
-            match child.try_wait() {
-                Ok(Some(_)) => {
-                    // Process finished, remove it
-                    inner.running_processes.remove(&project_id);
-                }
-                Ok(None) => {
-                    // Still running
-                    return Ok(());
-                }
-                Err(_) => {
-                    inner.running_processes.remove(&project_id);
-                }
-            }
-        }
+    project::restore_project(&handle, project_id).await
+}
+
+#[tauri::command]
+pub async fn purge_project(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    project_id: Uuid,
+) -> AppResult<Uuid> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    project::purge_project(&handle, project_id).await
+}
+
+#[tauri::command]
+pub async fn empty_trash(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+) -> AppResult<Vec<Uuid>> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    project::empty_trash(&handle).await
+}
+
+#[tauri::command]
+pub async fn scan_for_projects(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    root: PathBuf,
+    max_depth: Option<usize>,
+    ignore_globs: Option<Vec<String>>,
+) -> AppResult<Vec<ScanCandidate>> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    let root = project::sanitize_path_buf(root);
+    if !root.is_dir() {
+        return Err(AppError::Validation(format!(
+            "scan root is not a directory: {}",
+            root.display()
+        )));
     }
+    let ignore = scan::compile_globs(&ignore_globs.unwrap_or_default())?;
+    let max_depth = max_depth.unwrap_or(scan::DEFAULT_SCAN_DEPTH);
 
-    if let Some(child) = project::launch_project(&handle, &project).await? {
-        let mut inner = state.inner.write().await;
-        inner.running_processes.insert(project_id, child);
+    let mut candidates =
+        tokio::task::spawn_blocking(move || scan::find_projects(&root, max_depth, &ignore))
+            .await
+            .map_err(|err| AppError::Io(std::io::Error::other(err)))?;
+    let known = project::project_paths(&handle).await?;
+    for candidate in &mut candidates {
+        candidate.existing_project_id = known.get(&project::path_key(&candidate.path)).copied();
     }
-    
-    Ok(())
+    Ok(candidates)
 }
 
 #[tauri::command]
-pub async fn stop_project(
+pub async fn import_projects(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    imports: Vec<ProjectImport>,
+) -> AppResult<Vec<Project>> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    project::import_projects(&handle, imports).await
+}
+
+#[tauri::command]
+pub async fn set_project_flags(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    project_id: Uuid,
+    favorite: Option<bool>,
+    pinned: Option<bool>,
+) -> AppResult<Project> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    project::set_project_flags(&handle, project_id, favorite, pinned).await
+}
+
+#[tauri::command]
+pub async fn set_project_tags(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    project_id: Uuid,
+    tags: Vec<String>,
+) -> AppResult<Project> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    tags::set_project_tags(&handle, project_id, tags).await
+}
+
+#[tauri::command]
+pub async fn list_tags(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+) -> AppResult<Vec<TagSummary>> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    tags::list_tags(&handle).await
+}
+
+#[tauri::command]
+pub async fn create_tag(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    payload: TagInput,
+) -> AppResult<Tag> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    tags::create_tag(&handle, payload).await
+}
+
+#[tauri::command]
+pub async fn update_tag(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    tag_id: Uuid,
+    payload: TagInput,
+) -> AppResult<Tag> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    tags::update_tag(&handle, tag_id, payload).await
+}
+
+#[tauri::command]
+pub async fn merge_tags(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    source_ids: Vec<Uuid>,
+    target_id: Uuid,
+) -> AppResult<Tag> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    tags::merge_tags(&handle, &source_ids, target_id).await
+}
+
+#[tauri::command]
+pub async fn delete_tag(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    tag_id: Uuid,
+) -> AppResult<()> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    tags::delete_tag(&handle, tag_id).await
+}
+
+#[tauri::command]
+pub async fn list_project_notes(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    project_id: Uuid,
+) -> AppResult<Vec<ProjectNote>> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    notes::list_notes(&handle, Some(project_id)).await
+}
+
+#[tauri::command]
+pub async fn save_project_note(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    project_id: Uuid,
+    payload: ProjectNoteInput,
+) -> AppResult<ProjectNote> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    notes::save_note(&handle, project_id, payload).await
+}
+
+#[tauri::command]
+pub async fn delete_project_note(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    note_id: Uuid,
+) -> AppResult<Uuid> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    notes::delete_note(&handle, note_id).await
+}
+
+#[tauri::command]
+pub async fn export_workspace(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    path: PathBuf,
+) -> AppResult<WorkspaceExport> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    export::export_workspace(&handle, &project::sanitize_path_buf(path)).await
+}
+
+#[tauri::command]
+pub async fn check_project_paths(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+) -> AppResult<Vec<ProjectPathHealth>> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    state.check_project_paths(&handle).await
+}
+
+#[tauri::command]
+pub async fn find_project_relocations(
     state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    project_id: Uuid,
+    roots: Option<Vec<PathBuf>>,
+) -> AppResult<Vec<RelocationCandidate>> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    health::find_relocations(&handle, project_id, roots.unwrap_or_default()).await
+}
+
+#[tauri::command]
+pub async fn relocate_project(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
     project_id: Uuid,
+    new_path: PathBuf,
+) -> AppResult<Project> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    let project = project::relocate_project(&handle, project_id, new_path).await?;
+    state.mark_path_available(project_id).await;
+    Ok(project)
+}
+
+#[tauri::command]
+pub async fn add_watched_folder(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    workspace_id: Uuid,
+    payload: WatchedFolderInput,
+) -> AppResult<WorkspaceRecord> {
+    let (record, folder) = state.add_watched_folder(workspace_id, payload).await?;
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn(async move {
+        state
+            .reconcile_watched_folder(&app, workspace_id, &folder)
+            .await;
+    });
+    Ok(record)
+}
+
+#[tauri::command]
+pub async fn remove_watched_folder(
+    state: State<'_, AppState>,
+    workspace_id: Uuid,
+    watch_id: Uuid,
+) -> AppResult<WorkspaceRecord> {
+    state.remove_watched_folder(workspace_id, watch_id).await
+}
+
+#[tauri::command]
+pub async fn list_project_suggestions(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+) -> AppResult<Vec<ProjectSuggestion>> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    watch::list_suggestions(&handle).await
+}
+
+#[tauri::command]
+pub async fn accept_project_suggestions(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    suggestion_ids: Vec<Uuid>,
+) -> AppResult<Vec<Project>> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    watch::accept_suggestions(&handle, &suggestion_ids).await
+}
+
+#[tauri::command]
+pub async fn dismiss_project_suggestions(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    suggestion_ids: Vec<Uuid>,
 ) -> AppResult<()> {
-    let mut inner = state.inner.write().await;
-    if let Some(child) = inner.running_processes.get_mut(&project_id) {
-        project::stop_project(child)?;
-        inner.running_processes.remove(&project_id);
+    let handle = state.workspace_handle(workspace_id).await?;
+    watch::dismiss_suggestions(&handle, &suggestion_ids).await
+}
+
+#[tauri::command]
+pub async fn rescan_project_stack(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    project_id: Option<Uuid>,
+) -> AppResult<Vec<Project>> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    project::rescan_stacks(&handle, project_id).await
+}
+
+#[tauri::command]
+pub async fn discover_tasks(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    project_id: Uuid,
+) -> AppResult<Vec<DiscoveredTask>> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    let project = project::get_project(&handle, project_id).await?;
+    if !project.path.is_dir() {
+        return Err(AppError::Validation(format!(
+            "project path not found: {}",
+            project.path.display()
+        )));
     }
-    Ok(())
+    Ok(discovery::discover_tasks(&project.path))
 }
 
 #[tauri::command]
-pub async fn get_running_projects(state: State<'_, AppState>) -> Result<Vec<Uuid>, AppError> {
-    let mut inner = state.inner.write().await;
-    let mut running = Vec::new();
-    let mut to_remove = Vec::new();
-
-    for (id, child) in inner.running_processes.iter_mut() {
-        match child.try_wait() {
-            Ok(Some(_)) => {
-                to_remove.push(*id);
-            }
-            Ok(None) => {
-                running.push(*id);
-            }
-            Err(_) => {
-                to_remove.push(*id);
-            }
+pub async fn launch_project(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    project_id: Uuid,
+    config_id: Option<Uuid>,
+    name: Option<String>,
+) -> AppResult<Option<ProcessInstance>> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    let project = project::get_project(&handle, project_id).await?;
+
+    // Reap first so an instance that just exited does not block its name
+    state.reap_processes().await;
+    let request = LaunchRequest {
+        config_id,
+        name,
+        instance_id: None,
+    };
+    state.launch_tracked(&app, &handle, &project, request).await
+}
+
+#[tauri::command]
+pub async fn stop_project(
+    state: State<'_, AppState>,
+    project_id: Uuid,
+    instance_id: Option<Uuid>,
+) -> AppResult<Vec<ProcessStopResult>> {
+    let instance_ids: Vec<Uuid> = {
+        let mut inner = state.inner.write().await;
+        if instance_id.is_none() {
+            inner.supervisor.cancel_project(project_id);
+        }
+        inner
+            .running_processes
+            .values()
+            .map(|entry| &entry.instance)
+            .filter(|instance| instance.project_id == project_id)
+            .filter(|instance| instance_id.is_none_or(|id| id == instance.id))
+            .map(|instance| instance.id)
+            .collect()
+    };
+    if let Some(id) = instance_id {
+        if instance_ids.is_empty() {
+            return Err(AppError::ProcessNotFound(id.to_string()));
         }
     }
+    state.stop_processes(&instance_ids).await
+}
 
-    for id in to_remove {
-        inner.running_processes.remove(&id);
-    }
+#[tauri::command]
+pub async fn get_running_projects(state: State<'_, AppState>) -> Result<Vec<Uuid>, AppError> {
+    let mut project_ids: Vec<Uuid> = state
+        .reap_processes()
+        .await
+        .into_iter()
+        .map(|instance| instance.project_id)
+        .collect();
+    project_ids.sort();
+    project_ids.dedup();
+    Ok(project_ids)
+}
 
-    Ok(running)
+#[tauri::command]
+pub async fn get_running_processes(
+    state: State<'_, AppState>,
+    project_id: Option<Uuid>,
+) -> Result<Vec<ProcessInstance>, AppError> {
+    let mut instances: Vec<ProcessInstance> = state
+        .reap_processes()
+        .await
+        .into_iter()
+        .filter(|instance| project_id.is_none_or(|id| id == instance.project_id))
+        .collect();
+    instances.sort_by_key(|instance| instance.started_at);
+    Ok(instances)
+}
+
+#[tauri::command]
+pub async fn list_launch_sessions(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+    project_id: Option<Uuid>,
+    instance_id: Option<Uuid>,
+    limit: Option<u32>,
+) -> AppResult<Vec<LaunchSession>> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    session::list_sessions(&handle, project_id, instance_id, limit.unwrap_or(100)).await
+}
+
+#[tauri::command]
+pub async fn get_session_summaries(
+    state: State<'_, AppState>,
+    workspace_id: Option<Uuid>,
+) -> AppResult<Vec<SessionSummary>> {
+    let handle = state.workspace_handle(workspace_id).await?;
+    session::get_session_summaries(&handle).await
+}
+
+#[tauri::command]
+pub async fn get_process_output(
+    state: State<'_, AppState>,
+    instance_id: Uuid,
+    since: Option<u64>,
+) -> AppResult<ProcessOutput> {
+    let inner = state.inner.read().await;
+    let buffer = inner
+        .process_output
+        .get(&instance_id)
+        .ok_or_else(|| AppError::ProcessNotFound(instance_id.to_string()))?;
+    let output = process::lock_output(buffer).snapshot(instance_id, since);
+    Ok(output)
+}
+
+#[tauri::command]
+pub async fn get_project_output(
+    state: State<'_, AppState>,
+    project_id: Uuid,
+    since: Option<u64>,
+) -> AppResult<Vec<ProcessOutput>> {
+    let inner = state.inner.read().await;
+    // Each instance numbers its lines on its own, `since` applies to each
+    let mut outputs: Vec<ProcessOutput> = inner
+        .process_output
+        .iter()
+        .filter_map(|(instance_id, buffer)| {
+            let buffer = process::lock_output(buffer);
+            (buffer.project_id() == project_id).then(|| buffer.snapshot(*instance_id, since))
+        })
+        .collect();
+    outputs.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(outputs)
 }
 
 #[tauri::command]
@@ -180,3 +651,8 @@ pub async fn update_settings(
 ) -> AppResult<AppSettings> {
     state.update_settings(payload).await
 }
+
+#[tauri::command]
+pub async fn get_preset_usage(state: State<'_, AppState>) -> AppResult<Vec<PresetUsage>> {
+    state.preset_usage().await
+}
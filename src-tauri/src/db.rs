@@ -1,7 +1,128 @@
-use crate::error::AppResult;
-use sqlx::{sqlite::SqliteJournalMode, SqlitePool};
+use std::{fs, path::Path};
 
-pub async fn init_workspace_schema(pool: &SqlitePool) -> AppResult<()> {
+use crate::{
+    error::{AppError, AppResult},
+    search,
+};
+use chrono::Utc;
+use sqlx::{sqlite::SqliteJournalMode, SqliteConnection, SqlitePool};
+use uuid::Uuid;
+
+pub const DEFAULT_LAUNCH_CONFIG_NAME: &str = "Default";
+
+/// Schema version written by this build, stored in `PRAGMA user_version`.
+/// Bump it together with a new step in [`apply_migration`].
+pub const SCHEMA_VERSION: i64 = 2;
+/// Pre-migration backups kept next to each workspace database.
+const MAX_SCHEMA_BACKUPS: usize = 3;
+
+/// Migrates the workspace database at `database_path` and makes sure its
+/// search index is complete.
+pub async fn init_workspace_schema(pool: &SqlitePool, database_path: &Path) -> AppResult<()> {
+    migrate_workspace_schema(pool, database_path).await?;
+    search::ensure_index(pool).await
+}
+
+/// Brings the workspace database at `database_path` up to
+/// [`SCHEMA_VERSION`], one transaction per version. The database is backed
+/// up first unless it is new; databases written by a newer build are
+/// refused untouched.
+pub async fn migrate_workspace_schema(pool: &SqlitePool, database_path: &Path) -> AppResult<()> {
+    let version = schema_version(pool).await?;
+    if version > SCHEMA_VERSION {
+        return Err(AppError::SchemaTooNew(
+            database_path.to_path_buf(),
+            version,
+            SCHEMA_VERSION,
+        ));
+    }
+    if version < SCHEMA_VERSION {
+        let is_empty: bool = sqlx::query_scalar("SELECT count(*) = 0 FROM sqlite_master")
+            .fetch_one(pool)
+            .await?;
+        if !is_empty {
+            backup_database(pool, database_path, version).await?;
+        }
+        for next in version + 1..=SCHEMA_VERSION {
+            let mut tx = pool.begin().await?;
+            apply_migration(&mut tx, next).await?;
+            // The version lives in the database header, so it commits with the step
+            sqlx::query(&format!("PRAGMA user_version = {next}"))
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            log::info!(
+                "migrated {} to schema version {next}",
+                database_path.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+pub async fn schema_version(pool: &SqlitePool) -> AppResult<i64> {
+    Ok(sqlx::query_scalar("PRAGMA user_version")
+        .fetch_one(pool)
+        .await?)
+}
+
+async fn apply_migration(conn: &mut SqliteConnection, version: i64) -> AppResult<()> {
+    match version {
+        1 => create_base_schema(conn).await,
+        2 => {
+            sqlx::query(
+                "CREATE INDEX IF NOT EXISTS idx_launch_history_project \
+                 ON launch_history(project_id, launched_at)",
+            )
+            .execute(conn)
+            .await?;
+            Ok(())
+        }
+        _ => unreachable!("no migration to schema version {version}"),
+    }
+}
+
+/// Copies the database to `<file>.v<version>-<timestamp>.bak` and prunes
+/// older copies beyond [`MAX_SCHEMA_BACKUPS`].
+async fn backup_database(pool: &SqlitePool, database_path: &Path, version: i64) -> AppResult<()> {
+    let (Some(dir), Some(file_name)) = (database_path.parent(), database_path.file_name()) else {
+        return Ok(());
+    };
+    let prefix = format!("{}.v", file_name.to_string_lossy());
+    let backup_path = dir.join(format!(
+        "{prefix}{version}-{}.bak",
+        Utc::now().format("%Y%m%d%H%M%S")
+    ));
+    if !backup_path.exists() {
+        // Unlike a file copy, this is consistent with pending WAL frames
+        sqlx::query("VACUUM INTO ?")
+            .bind(backup_path.to_string_lossy().to_string())
+            .execute(pool)
+            .await?;
+    }
+
+    let mut backups: Vec<_> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy())
+                .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".bak"))
+        })
+        .collect();
+    backups.sort_by_key(|path| fs::metadata(path).and_then(|meta| meta.modified()).ok());
+    let excess = backups.len().saturating_sub(MAX_SCHEMA_BACKUPS);
+    for old in backups.into_iter().take(excess) {
+        if let Err(err) = fs::remove_file(&old) {
+            log::warn!("failed to remove old backup {}: {err}", old.display());
+        }
+    }
+    Ok(())
+}
+
+/// Schema version 1. Databases from before versioning report version 0 at
+/// any point of the old ad-hoc schema, so every statement is idempotent.
+async fn create_base_schema(conn: &mut SqliteConnection) -> AppResult<()> {
     sqlx::query(
         r#"
     CREATE TABLE IF NOT EXISTS projects (
@@ -10,21 +131,163 @@ pub async fn init_workspace_schema(pool: &SqlitePool) -> AppResult<()> {
       path TEXT NOT NULL UNIQUE,
       description TEXT,
       open_config TEXT NOT NULL,
+      stop_grace_period_secs INTEGER,
+      restart_policy TEXT,
+      stack TEXT,
+      git_remote TEXT,
+      favorite INTEGER NOT NULL DEFAULT 0,
+      pinned INTEGER NOT NULL DEFAULT 0,
+      deleted_at TEXT,
       created_at TEXT NOT NULL,
       updated_at TEXT NOT NULL
     );
 
+    CREATE TABLE IF NOT EXISTS tags (
+      id TEXT PRIMARY KEY,
+      name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+      color TEXT,
+      created_at TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS project_tags (
+      project_id TEXT NOT NULL,
+      tag_id TEXT NOT NULL,
+      PRIMARY KEY(project_id, tag_id),
+      FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE,
+      FOREIGN KEY(tag_id) REFERENCES tags(id) ON DELETE CASCADE
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_project_tags_tag
+      ON project_tags(tag_id);
+
+    CREATE TABLE IF NOT EXISTS project_notes (
+      id TEXT PRIMARY KEY,
+      project_id TEXT NOT NULL,
+      title TEXT NOT NULL,
+      body TEXT NOT NULL,
+      created_at TEXT NOT NULL,
+      updated_at TEXT NOT NULL,
+      FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_project_notes_project
+      ON project_notes(project_id, updated_at);
+
+    CREATE TABLE IF NOT EXISTS launch_configs (
+      id TEXT PRIMARY KEY,
+      project_id TEXT NOT NULL,
+      name TEXT NOT NULL,
+      position INTEGER NOT NULL,
+      is_default INTEGER NOT NULL DEFAULT 0,
+      config TEXT NOT NULL,
+      FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_launch_configs_project
+      ON launch_configs(project_id, position);
+
+    CREATE TABLE IF NOT EXISTS project_suggestions (
+      id TEXT PRIMARY KEY,
+      watch_id TEXT NOT NULL,
+      path TEXT NOT NULL UNIQUE,
+      name TEXT NOT NULL,
+      markers TEXT NOT NULL,
+      detected_at TEXT NOT NULL,
+      dismissed INTEGER NOT NULL DEFAULT 0
+    );
+
     CREATE TABLE IF NOT EXISTS launch_history (
       id INTEGER PRIMARY KEY AUTOINCREMENT,
       project_id TEXT NOT NULL,
       launched_at TEXT NOT NULL,
       FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
     );
+
+    CREATE TABLE IF NOT EXISTS launch_sessions (
+      id TEXT PRIMARY KEY,
+      project_id TEXT NOT NULL,
+      instance_id TEXT,
+      process_name TEXT,
+      config_id TEXT,
+      status TEXT NOT NULL,
+      started_at TEXT NOT NULL,
+      ended_at TEXT,
+      pid INTEGER,
+      exit_code INTEGER,
+      signal INTEGER,
+      error TEXT,
+      FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_launch_sessions_project
+      ON launch_sessions(project_id, started_at);
     "#,
     )
-    .execute(pool)
+    .execute(&mut *conn)
+    .await?;
+
+    ensure_column(conn, "projects", "stop_grace_period_secs", "INTEGER").await?;
+    ensure_column(conn, "projects", "restart_policy", "TEXT").await?;
+    ensure_column(conn, "projects", "stack", "TEXT").await?;
+    ensure_column(conn, "projects", "git_remote", "TEXT").await?;
+    ensure_column(conn, "projects", "favorite", "INTEGER NOT NULL DEFAULT 0").await?;
+    ensure_column(conn, "projects", "pinned", "INTEGER NOT NULL DEFAULT 0").await?;
+    ensure_column(conn, "projects", "deleted_at", "TEXT").await?;
+    ensure_column(conn, "launch_sessions", "instance_id", "TEXT").await?;
+    ensure_column(conn, "launch_sessions", "process_name", "TEXT").await?;
+    ensure_column(conn, "launch_sessions", "config_id", "TEXT").await?;
+    migrate_open_configs(conn).await?;
+
+    Ok(())
+}
+
+/// Turns the `open_config` column of projects without launch configurations
+/// into their default entry. The column itself stays and mirrors the default
+/// configuration, so older builds can still read the database.
+async fn migrate_open_configs(conn: &mut SqliteConnection) -> AppResult<()> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT p.id, p.open_config
+        FROM projects p
+        WHERE NOT EXISTS (SELECT 1 FROM launch_configs c WHERE c.project_id = p.id)
+        "#,
+    )
+    .fetch_all(&mut *conn)
     .await?;
+    for (project_id, open_config) in rows {
+        sqlx::query(
+            r#"
+            INSERT INTO launch_configs (id, project_id, name, position, is_default, config)
+            VALUES (?, ?, ?, 0, 1, ?)
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&project_id)
+        .bind(DEFAULT_LAUNCH_CONFIG_NAME)
+        .bind(&open_config)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
 
+/// Adds a column to databases created before it was part of the schema.
+async fn ensure_column(
+    conn: &mut SqliteConnection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> AppResult<()> {
+    let exists: bool =
+        sqlx::query_scalar("SELECT count(*) > 0 FROM pragma_table_info(?) WHERE name = ?")
+            .bind(table)
+            .bind(column)
+            .fetch_one(&mut *conn)
+            .await?;
+    if !exists {
+        let statement = format!("ALTER TABLE {table} ADD COLUMN {column} {definition}");
+        sqlx::query(&statement).execute(&mut *conn).await?;
+    }
     Ok(())
 }
 
@@ -36,4 +299,53 @@ pub fn apply_default_pragmas(
         .journal_mode(SqliteJournalMode::Wal)
         .synchronous(sqlx::sqlite::SqliteSynchronous::Normal)
         .busy_timeout(std::time::Duration::from_secs(5))
+        // Spelled out so that `ON DELETE CASCADE` never depends on a default
+        .foreign_keys(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+
+    async fn memory_pool() -> SqlitePool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn new_database_is_migrated_to_the_current_version() {
+        let pool = memory_pool().await;
+        migrate_workspace_schema(&pool, Path::new("projects.sqlite"))
+            .await
+            .unwrap();
+        assert_eq!(schema_version(&pool).await.unwrap(), SCHEMA_VERSION);
+    }
+
+    #[tokio::test]
+    async fn newer_schema_version_is_refused_untouched() {
+        let pool = memory_pool().await;
+        let newer = SCHEMA_VERSION + 1;
+        sqlx::query(&format!("PRAGMA user_version = {newer}"))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let result = migrate_workspace_schema(&pool, Path::new("projects.sqlite")).await;
+        assert!(matches!(
+            result,
+            Err(AppError::SchemaTooNew(_, found, supported))
+                if found == newer && supported == SCHEMA_VERSION
+        ));
+        assert_eq!(schema_version(&pool).await.unwrap(), newer);
+        let tables: i64 = sqlx::query_scalar("SELECT count(*) FROM sqlite_master")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(tables, 0);
+    }
 }
@@ -0,0 +1,563 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+
+use crate::{
+    error::AppResult,
+    models::{DiscoveredTask, LaunchEnv, OpenConfig, TaskSource},
+};
+
+const MAKEFILE_NAMES: [&str; 3] = ["GNUmakefile", "makefile", "Makefile"];
+const JUSTFILE_NAMES: [&str; 3] = ["justfile", "Justfile", ".justfile"];
+
+type Discoverer = fn(&Path) -> AppResult<Vec<DiscoveredTask>>;
+
+/// Scans the manifests in `project_path` for tasks that can be launched.
+/// Unreadable or malformed manifests are skipped with a warning.
+pub fn discover_tasks(project_path: &Path) -> Vec<DiscoveredTask> {
+    let sources: [(&str, Discoverer); 8] = [
+        ("package.json", package_json_tasks),
+        ("Cargo.toml", cargo_tasks),
+        ("Makefile", makefile_tasks),
+        ("justfile", justfile_tasks),
+        ("pyproject.toml", pyproject_tasks),
+        ("Procfile", procfile_tasks),
+        ("Gradle", gradle_tasks),
+        ("Maven", maven_tasks),
+    ];
+
+    let mut tasks = Vec::new();
+    for (label, discover) in sources {
+        match discover(project_path) {
+            Ok(found) => tasks.extend(found),
+            Err(err) => log::warn!(
+                "failed to read {label} tasks in {}: {err}",
+                project_path.display()
+            ),
+        }
+    }
+    tasks
+}
+
+/// Node package managers, picked from the `packageManager` field or the
+/// lockfile in the project.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum PackageManager {
+    Npm,
+    Pnpm,
+    Yarn,
+    Bun,
+}
+
+impl PackageManager {
+    pub(crate) fn detect(project_path: &Path, manifest: &Value) -> Self {
+        let declared = manifest
+            .get("packageManager")
+            .and_then(Value::as_str)
+            .and_then(|value| value.split('@').next());
+        match declared {
+            Some("pnpm") => return Self::Pnpm,
+            Some("yarn") => return Self::Yarn,
+            Some("bun") => return Self::Bun,
+            Some("npm") => return Self::Npm,
+            _ => {}
+        }
+
+        let has = |name: &str| project_path.join(name).is_file();
+        if has("pnpm-lock.yaml") {
+            Self::Pnpm
+        } else if has("yarn.lock") {
+            Self::Yarn
+        } else if has("bun.lockb") || has("bun.lock") {
+            Self::Bun
+        } else {
+            Self::Npm
+        }
+    }
+
+    pub(crate) fn command(&self) -> &'static str {
+        match self {
+            Self::Npm => "npm",
+            Self::Pnpm => "pnpm",
+            Self::Yarn => "yarn",
+            Self::Bun => "bun",
+        }
+    }
+}
+
+fn package_json_tasks(project_path: &Path) -> AppResult<Vec<DiscoveredTask>> {
+    let Some(contents) = read_optional(&project_path.join("package.json"))? else {
+        return Ok(Vec::new());
+    };
+    let manifest: Value = serde_json::from_str(&contents)?;
+    let manager = PackageManager::detect(project_path, &manifest);
+
+    let Some(scripts) = manifest.get("scripts").and_then(Value::as_object) else {
+        return Ok(Vec::new());
+    };
+    Ok(scripts
+        .keys()
+        .map(|script| {
+            task(
+                script,
+                TaskSource::PackageJson,
+                format!("{} run {}", manager.command(), quote(script)),
+            )
+        })
+        .collect())
+}
+
+fn cargo_tasks(project_path: &Path) -> AppResult<Vec<DiscoveredTask>> {
+    let Some(contents) = read_optional(&project_path.join("Cargo.toml"))? else {
+        return Ok(Vec::new());
+    };
+    let manifest: toml::Table = toml::from_str(&contents)?;
+    let Some(package) = manifest.get("package").and_then(toml::Value::as_table) else {
+        // Virtual workspace manifests have nothing to run on their own
+        return Ok(Vec::new());
+    };
+
+    let mut bins = BTreeSet::new();
+    if let Some(targets) = manifest.get("bin").and_then(toml::Value::as_array) {
+        bins.extend(
+            targets
+                .iter()
+                .filter_map(|target| target.get("name").and_then(toml::Value::as_str))
+                .map(str::to_string),
+        );
+    }
+    let autobins = package
+        .get("autobins")
+        .and_then(toml::Value::as_bool)
+        .unwrap_or(true);
+    if autobins {
+        if project_path.join("src/main.rs").is_file() {
+            if let Some(name) = package.get("name").and_then(toml::Value::as_str) {
+                bins.insert(name.to_string());
+            }
+        }
+        bins.extend(file_stems(&project_path.join("src/bin"), "rs"));
+    }
+
+    Ok(bins
+        .into_iter()
+        .map(|bin| {
+            let command = format!("cargo run --bin {}", quote(&bin));
+            task(&bin, TaskSource::Cargo, command)
+        })
+        .collect())
+}
+
+fn makefile_tasks(project_path: &Path) -> AppResult<Vec<DiscoveredTask>> {
+    let Some(contents) = read_first(project_path, &MAKEFILE_NAMES)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut targets = BTreeSet::new();
+    for line in contents.lines() {
+        if line.starts_with(['\t', ' ', '#', '.']) {
+            continue;
+        }
+        let Some((names, rest)) = line.split_once(':') else {
+            continue;
+        };
+        // `NAME := value` and `NAME ::= value` are assignments
+        if rest.starts_with('=') || rest.starts_with(":=") || names.contains(['=', '$', '%']) {
+            continue;
+        }
+        targets.extend(names.split_whitespace().map(str::to_string));
+    }
+
+    Ok(targets
+        .into_iter()
+        .map(|target| {
+            let command = format!("make {}", quote(&target));
+            task(&target, TaskSource::Makefile, command)
+        })
+        .collect())
+}
+
+fn justfile_tasks(project_path: &Path) -> AppResult<Vec<DiscoveredTask>> {
+    let Some(contents) = read_first(project_path, &JUSTFILE_NAMES)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut recipes = Vec::new();
+    for line in contents.lines() {
+        if line.starts_with([' ', '\t', '#', '[']) {
+            continue;
+        }
+        let Some((head, rest)) = line.split_once(':') else {
+            continue;
+        };
+        if rest.starts_with('=') {
+            continue;
+        }
+        let mut words = head.split_whitespace();
+        let Some(name) = words.next().map(|word| word.trim_start_matches('@')) else {
+            continue;
+        };
+        let is_keyword = matches!(
+            name,
+            "set" | "alias" | "export" | "import" | "mod" | "unexport"
+        );
+        let is_identifier = name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-');
+        // Recipes starting with `_` are private
+        if is_keyword || !is_identifier || name.is_empty() || name.starts_with('_') {
+            continue;
+        }
+        if !recipes.iter().any(|recipe: &String| recipe == name) {
+            recipes.push(name.to_string());
+        }
+    }
+
+    Ok(recipes
+        .into_iter()
+        .map(|recipe| {
+            let command = format!("just {}", quote(&recipe));
+            task(&recipe, TaskSource::Justfile, command)
+        })
+        .collect())
+}
+
+fn pyproject_tasks(project_path: &Path) -> AppResult<Vec<DiscoveredTask>> {
+    let Some(contents) = read_optional(&project_path.join("pyproject.toml"))? else {
+        return Ok(Vec::new());
+    };
+    let manifest: toml::Value = toml::from_str(&contents)?;
+    let table_keys = |path: &[&str]| -> Vec<String> {
+        let mut value = &manifest;
+        for key in path {
+            match value.get(key) {
+                Some(next) => value = next,
+                None => return Vec::new(),
+            }
+        }
+        value
+            .as_table()
+            .map(|table| table.keys().cloned().collect())
+            .unwrap_or_default()
+    };
+
+    let runner = if manifest
+        .get("tool")
+        .and_then(|tool| tool.get("poetry"))
+        .is_some()
+    {
+        "poetry run "
+    } else if project_path.join("uv.lock").is_file() {
+        "uv run "
+    } else if project_path.join("pdm.lock").is_file() {
+        "pdm run "
+    } else {
+        ""
+    };
+
+    let mut scripts = BTreeSet::new();
+    scripts.extend(table_keys(&["project", "scripts"]));
+    scripts.extend(table_keys(&["tool", "poetry", "scripts"]));
+    let mut tasks: Vec<DiscoveredTask> = scripts
+        .into_iter()
+        .map(|script| {
+            let command = format!("{runner}{}", quote(&script));
+            task(&script, TaskSource::Pyproject, command)
+        })
+        .collect();
+
+    // PDM scripts only exist inside `pdm run`
+    for script in table_keys(&["tool", "pdm", "scripts"]) {
+        if script == "_" {
+            continue;
+        }
+        let command = format!("pdm run {}", quote(&script));
+        tasks.push(task(&script, TaskSource::Pyproject, command));
+    }
+    Ok(tasks)
+}
+
+fn procfile_tasks(project_path: &Path) -> AppResult<Vec<DiscoveredTask>> {
+    let Some(contents) = read_optional(&project_path.join("Procfile"))? else {
+        return Ok(Vec::new());
+    };
+    Ok(contents
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, command)| (name.trim(), command.trim()))
+        .filter(|(name, command)| !name.is_empty() && !command.is_empty())
+        .map(|(name, command)| task(name, TaskSource::Procfile, command.to_string()))
+        .collect())
+}
+
+fn gradle_tasks(project_path: &Path) -> AppResult<Vec<DiscoveredTask>> {
+    let wrapper = if cfg!(windows) {
+        "gradlew.bat"
+    } else {
+        "gradlew"
+    };
+    if !project_path.join(wrapper).is_file() {
+        return Ok(Vec::new());
+    }
+    let build = read_first(project_path, &["build.gradle.kts", "build.gradle"])?;
+    let mut goals = vec!["build", "test"];
+    if build
+        .as_deref()
+        .is_some_and(|text| text.contains("org.springframework.boot"))
+    {
+        goals.insert(0, "bootRun");
+    } else if build
+        .as_deref()
+        .is_some_and(|text| text.contains("application"))
+    {
+        goals.insert(0, "run");
+    }
+    let program = wrapper_command(wrapper);
+    Ok(goals
+        .into_iter()
+        .map(|goal| task(goal, TaskSource::Gradle, format!("{program} {goal}")))
+        .collect())
+}
+
+fn maven_tasks(project_path: &Path) -> AppResult<Vec<DiscoveredTask>> {
+    let wrapper = if cfg!(windows) { "mvnw.cmd" } else { "mvnw" };
+    if !project_path.join(wrapper).is_file() {
+        return Ok(Vec::new());
+    }
+    let pom = read_optional(&project_path.join("pom.xml"))?;
+    let mut goals = vec!["package", "test"];
+    if pom
+        .as_deref()
+        .is_some_and(|text| text.contains("spring-boot-maven-plugin"))
+    {
+        goals.insert(0, "spring-boot:run");
+    }
+    let program = wrapper_command(wrapper);
+    Ok(goals
+        .into_iter()
+        .map(|goal| task(goal, TaskSource::Maven, format!("{program} {goal}")))
+        .collect())
+}
+
+/// Tasks run through the login shell so version managers such as `nvm` or
+/// `pyenv` are set up the way they are in a terminal. `${` is escaped so the
+/// command runs as written instead of expanding placeholders.
+fn task(name: &str, source: TaskSource, command: String) -> DiscoveredTask {
+    DiscoveredTask {
+        name: name.to_string(),
+        source,
+        config: OpenConfig::ShellCommand {
+            command: command.replace("${", "$${"),
+            env: LaunchEnv::default(),
+            cwd: None,
+        },
+    }
+}
+
+fn wrapper_command(wrapper: &str) -> String {
+    if cfg!(windows) {
+        wrapper.to_string()
+    } else {
+        format!("./{wrapper}")
+    }
+}
+
+/// Quotes `value` for the shell when it contains anything beyond the
+/// characters commonly found in task names.
+fn quote(value: &str) -> String {
+    let plain = value
+        .chars()
+        .all(|ch| ch.is_ascii_alphanumeric() || "-_.:/@+=".contains(ch));
+    if plain {
+        value.to_string()
+    } else if cfg!(windows) {
+        format!("\"{}\"", value.replace('"', "\\\""))
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+fn read_optional(path: &Path) -> AppResult<Option<String>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    Ok(Some(fs::read_to_string(path)?))
+}
+
+fn read_first(dir: &Path, names: &[&str]) -> AppResult<Option<String>> {
+    match names
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+    {
+        Some(path) => read_optional(&path),
+        None => Ok(None),
+    }
+}
+
+fn file_stems(dir: &Path, extension: &str) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path: &PathBuf| path.extension().is_some_and(|ext| ext == extension))
+        .filter_map(|path| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::placeholders::PlaceholderContext;
+
+    fn project(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, contents) in files {
+            let path = dir.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        dir
+    }
+
+    fn commands(tasks: &[DiscoveredTask]) -> Vec<(&str, &str)> {
+        tasks
+            .iter()
+            .map(|task| match &task.config {
+                OpenConfig::ShellCommand { command, .. } => (task.name.as_str(), command.as_str()),
+                _ => panic!("task {} is not a shell command", task.name),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn package_json_scripts_use_the_detected_package_manager() {
+        let dir = project(&[
+            (
+                "package.json",
+                r#"{"scripts": {"dev": "vite", "build:prod": "vite build"}}"#,
+            ),
+            ("pnpm-lock.yaml", ""),
+        ]);
+        let tasks = package_json_tasks(dir.path()).unwrap();
+        assert_eq!(
+            commands(&tasks),
+            [
+                ("build:prod", "pnpm run build:prod"),
+                ("dev", "pnpm run dev")
+            ]
+        );
+    }
+
+    #[test]
+    fn cargo_binaries_include_explicit_and_automatic_targets() {
+        let dir = project(&[
+            (
+                "Cargo.toml",
+                "[package]\nname = \"app\"\n\n[[bin]]\nname = \"tool\"\npath = \"tools/tool.rs\"\n",
+            ),
+            ("src/main.rs", ""),
+            ("src/bin/worker.rs", ""),
+        ]);
+        let tasks = cargo_tasks(dir.path()).unwrap();
+        assert_eq!(
+            commands(&tasks),
+            [
+                ("app", "cargo run --bin app"),
+                ("tool", "cargo run --bin tool"),
+                ("worker", "cargo run --bin worker"),
+            ]
+        );
+
+        let workspace = project(&[("Cargo.toml", "[workspace]\nmembers = [\"app\"]\n")]);
+        assert!(cargo_tasks(workspace.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn makefile_targets_skip_variables_and_special_targets() {
+        let dir = project(&[(
+            "Makefile",
+            "CC := gcc\nFLAGS ::= -O2\n.PHONY: build\n%.o: %.c\n\tgcc -c $<\nbuild test: deps\n\tmake\n# lint: x\n",
+        )]);
+        let tasks = makefile_tasks(dir.path()).unwrap();
+        assert_eq!(
+            commands(&tasks),
+            [("build", "make build"), ("test", "make test")]
+        );
+    }
+
+    #[test]
+    fn justfile_recipes_skip_settings_and_private_recipes() {
+        let dir = project(&[(
+            "justfile",
+            "set shell := [\"bash\", \"-c\"]\nversion := \"1\"\nalias b := build\n\n\
+             [private]\n@build target='all':\n    cargo build\n_helper:\n    true\ntest: build\n",
+        )]);
+        let tasks = justfile_tasks(dir.path()).unwrap();
+        assert_eq!(
+            commands(&tasks),
+            [("build", "just build"), ("test", "just test")]
+        );
+    }
+
+    #[test]
+    fn pyproject_scripts_run_through_the_project_runner() {
+        let dir = project(&[(
+            "pyproject.toml",
+            "[tool.poetry.scripts]\nserve = \"app:main\"\n\n[tool.pdm.scripts]\n_ = \"x\"\nlint = \"ruff .\"\n",
+        )]);
+        let tasks = pyproject_tasks(dir.path()).unwrap();
+        assert_eq!(
+            commands(&tasks),
+            [("serve", "poetry run serve"), ("lint", "pdm run lint")]
+        );
+    }
+
+    #[test]
+    fn procfile_entries_keep_their_commands() {
+        let dir = project(&[(
+            "Procfile",
+            "web: bundle exec puma -p $PORT\n# worker: skipped\nrelease:\n",
+        )]);
+        let tasks = procfile_tasks(dir.path()).unwrap();
+        assert_eq!(commands(&tasks), [("web", "bundle exec puma -p $PORT")]);
+    }
+
+    #[test]
+    fn discovered_commands_escape_placeholder_syntax() {
+        let dir = project(&[(
+            "Procfile",
+            "web: puma -p ${PORT:-5000} --tag ${projectName}\n",
+        )]);
+        let tasks = procfile_tasks(dir.path()).unwrap();
+        assert_eq!(
+            commands(&tasks),
+            [("web", "puma -p $${PORT:-5000} --tag $${projectName}")]
+        );
+        let context = PlaceholderContext {
+            project_path: dir.path(),
+            project_name: "app",
+            workspace_name: "main",
+        };
+        assert_eq!(
+            context.expand_shell(commands(&tasks)[0].1).unwrap(),
+            "puma -p ${PORT:-5000} --tag ${projectName}"
+        );
+    }
+
+    #[test]
+    fn missing_manifests_yield_no_tasks() {
+        let dir = project(&[]);
+        assert!(discover_tasks(dir.path()).is_empty());
+    }
+}
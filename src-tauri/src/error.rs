@@ -7,8 +7,12 @@ use thiserror::Error;
 pub enum AppError {
     #[error("workspace not found: {0}")]
     WorkspaceNotFound(String),
+    #[error("workspace {0} is unavailable: {1}")]
+    WorkspaceUnavailable(String, String),
     #[error("project not found: {0}")]
     ProjectNotFound(String),
+    #[error("process not found: {0}")]
+    ProcessNotFound(String),
     #[error("validation error: {0}")]
     Validation(String),
     #[error("io error: {0}")]
@@ -17,10 +21,16 @@ pub enum AppError {
     Database(#[from] sqlx::Error),
     #[error("serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+    #[error("invalid manifest: {0}")]
+    Manifest(#[from] toml::de::Error),
     #[error("internal path not available: {0}")]
     PathUnavailable(PathBuf),
     #[error("failed to launch application: {0}")]
     Launch(String),
+    #[error(
+        "database {0} has schema version {1} but this build supports up to {2}, update the app to open it"
+    )]
+    SchemaTooNew(PathBuf, i64, i64),
 }
 
 pub type AppResult<T> = Result<T, AppError>;
@@ -0,0 +1,43 @@
+use std::{fs, path::Path};
+
+use chrono::Utc;
+
+use crate::{
+    error::{AppError, AppResult},
+    models::WorkspaceExport,
+    notes, project,
+    state::WorkspaceHandle,
+    tags,
+};
+
+/// Bumped whenever a field of [`WorkspaceExport`] changes meaning.
+const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// Collects the projects, tags and notes of a workspace. The trash is left
+/// out.
+pub async fn build_export(handle: &WorkspaceHandle) -> AppResult<WorkspaceExport> {
+    let projects = project::list_projects(handle).await?;
+    let mut notes = notes::list_notes(handle, None).await?;
+    notes.retain(|note| projects.iter().any(|project| project.id == note.project_id));
+    Ok(WorkspaceExport {
+        format_version: EXPORT_FORMAT_VERSION,
+        exported_at: Utc::now(),
+        workspace: handle.meta.clone(),
+        tags: tags::list_tags(handle).await?,
+        projects,
+        notes,
+    })
+}
+
+/// Writes the workspace to `path` as pretty-printed JSON.
+pub async fn export_workspace(handle: &WorkspaceHandle, path: &Path) -> AppResult<WorkspaceExport> {
+    if path.is_dir() {
+        return Err(AppError::Validation(format!(
+            "export path is a directory: {}",
+            path.display()
+        )));
+    }
+    let export = build_export(handle).await?;
+    fs::write(path, serde_json::to_string_pretty(&export)?)?;
+    Ok(export)
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use sqlx::SqlitePool;
+
+use crate::error::AppResult;
+
+/// Points a launch is worth depending on its age in days, in the spirit of
+/// Firefox's frecency: recent launches count most, old ones still a little,
+/// so the sum rewards both recency and launch count.
+fn launch_points(column: &str) -> String {
+    let age = format!("julianday('now') - julianday({column})");
+    format!(
+        "CASE WHEN {age} < 4 THEN 100 WHEN {age} < 14 THEN 70 WHEN {age} < 31 THEN 50 \
+         WHEN {age} < 90 THEN 30 ELSE 10 END"
+    )
+}
+
+/// Frecency of one project, or of every project when `project_id` is `None`,
+/// keyed by project id. Projects that were never launched are absent.
+pub async fn project_scores(
+    pool: &SqlitePool,
+    project_id: Option<&str>,
+) -> AppResult<HashMap<String, i64>> {
+    let rows: Vec<(String, i64)> = sqlx::query_as(&format!(
+        "SELECT project_id, SUM({}) FROM launch_history \
+         WHERE (?1 IS NULL OR project_id = ?1) GROUP BY project_id",
+        launch_points("launched_at")
+    ))
+    .bind(project_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Frecency of every launch configuration, keyed by configuration id, from
+/// the sessions it was launched in.
+pub async fn config_scores(pool: &SqlitePool) -> AppResult<HashMap<String, i64>> {
+    let rows: Vec<(String, i64)> = sqlx::query_as(&format!(
+        "SELECT config_id, SUM({}) FROM launch_sessions \
+         WHERE config_id IS NOT NULL AND status != 'spawn_failed' GROUP BY config_id",
+        launch_points("started_at")
+    ))
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Frecency of every launch preset, keyed by preset id: the launches of the
+/// configurations that currently refer to it.
+pub async fn preset_scores(pool: &SqlitePool) -> AppResult<HashMap<String, i64>> {
+    let rows: Vec<(String, i64)> = sqlx::query_as(&format!(
+        r#"
+        SELECT json_extract(c.config, '$.preset_id'), SUM({})
+        FROM launch_sessions s
+        JOIN launch_configs c ON c.id = s.config_id
+        WHERE json_extract(c.config, '$.mode') = 'preset' AND s.status != 'spawn_failed'
+        GROUP BY json_extract(c.config, '$.preset_id')
+        "#,
+        launch_points("s.started_at")
+    ))
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Scales a relevance score by frecency. The logarithm keeps a project
+/// launched daily from burying a better textual match.
+pub fn weigh(relevance: f64, frecency: i64) -> f64 {
+    relevance * (1.0 + 0.1 * (1.0 + frecency.max(0) as f64).ln())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weigh_favours_frecent_projects_without_burying_relevance() {
+        assert_eq!(weigh(0.8, 0), 0.8);
+        assert_eq!(weigh(0.8, -5), 0.8);
+        assert!(weigh(0.8, 10) > weigh(0.8, 1));
+        // A daily favourite does not outrank a clearly better match
+        assert!(weigh(0.5, 1_000) < weigh(0.9, 0));
+    }
+}
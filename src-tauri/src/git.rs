@@ -0,0 +1,313 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use tokio::task::JoinSet;
+
+use crate::models::{GitCommitSummary, GitRepoKind, GitStatus};
+
+/// How long a status stays valid. Short enough that the list catches up with
+/// commits made elsewhere, long enough that re-renders do not spawn git.
+const CACHE_TTL: Duration = Duration::from_secs(10);
+const MAX_CONCURRENT_QUERIES: usize = 8;
+
+/// Recent statuses keyed by project path.
+#[derive(Debug, Default)]
+pub struct StatusCache {
+    entries: HashMap<PathBuf, (Instant, GitStatus)>,
+}
+
+impl StatusCache {
+    pub fn get(&self, path: &Path) -> Option<GitStatus> {
+        self.entries
+            .get(path)
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < CACHE_TTL)
+            .map(|(_, status)| status.clone())
+    }
+
+    pub fn insert(&mut self, path: PathBuf, status: GitStatus) {
+        self.entries
+            .retain(|_, (fetched_at, _)| fetched_at.elapsed() < CACHE_TTL);
+        self.entries.insert(path, (Instant::now(), status));
+    }
+}
+
+/// Queries the repositories at `paths`, a few at a time.
+pub async fn query_all(paths: Vec<PathBuf>) -> HashMap<PathBuf, GitStatus> {
+    let mut statuses = HashMap::with_capacity(paths.len());
+    let mut pending = paths.into_iter();
+    let mut tasks = JoinSet::new();
+    loop {
+        while tasks.len() < MAX_CONCURRENT_QUERIES {
+            let Some(path) = pending.next() else {
+                break;
+            };
+            tasks.spawn_blocking(move || {
+                let status = query(&path);
+                (path, status)
+            });
+        }
+        let Some(result) = tasks.join_next().await else {
+            break;
+        };
+        match result {
+            Ok((path, status)) => {
+                statuses.insert(path, status);
+            }
+            Err(err) => log::warn!("git status query failed: {err}"),
+        }
+    }
+    statuses
+}
+
+/// Reads the status of the repository at `path` by running git. Never fails:
+/// problems are reported through [`GitStatus::kind`] and [`GitStatus::error`].
+pub fn query(path: &Path) -> GitStatus {
+    let mut status = GitStatus {
+        kind: GitRepoKind::NotRepository,
+        branch: None,
+        head_commit: None,
+        upstream: None,
+        ahead: 0,
+        behind: 0,
+        dirty: 0,
+        untracked: 0,
+        stash_count: 0,
+        last_commit: None,
+        error: None,
+        checked_at: Utc::now(),
+    };
+    if !path.is_dir() {
+        status.kind = GitRepoKind::Unavailable;
+        status.error = Some(format!("directory not found: {}", path.display()));
+        return status;
+    }
+
+    let layout = match run_git(
+        path,
+        &["rev-parse", "--is-bare-repository", "--is-inside-work-tree"],
+    ) {
+        Ok(output) => output,
+        Err(GitError::NotRepository) => return status,
+        Err(GitError::Failed(err)) => {
+            status.kind = GitRepoKind::Unavailable;
+            status.error = Some(err);
+            return status;
+        }
+    };
+    let mut flags = layout.lines().map(|line| line.trim() == "true");
+    let is_bare = flags.next().unwrap_or(false);
+    let in_work_tree = flags.next().unwrap_or(false);
+
+    if is_bare {
+        status.kind = GitRepoKind::Bare;
+        status.branch = run_git(path, &["symbolic-ref", "--quiet", "--short", "HEAD"])
+            .ok()
+            .map(|branch| branch.trim().to_string());
+    } else if in_work_tree {
+        status.kind = GitRepoKind::Worktree;
+        match run_git(path, &["status", "--porcelain=v2", "--branch"]) {
+            Ok(output) => parse_porcelain(&output, &mut status),
+            Err(GitError::NotRepository) => {}
+            Err(GitError::Failed(err)) => status.error = Some(err),
+        }
+    } else {
+        // Inside the `.git` directory of a work tree
+        return status;
+    }
+
+    status.stash_count = run_git(
+        path,
+        &["rev-list", "--walk-reflogs", "--count", "refs/stash"],
+    )
+    .ok()
+    .and_then(|count| count.trim().parse().ok())
+    .unwrap_or(0);
+    // Fails on a repository without commits, which simply has no last commit
+    if let Ok(output) = run_git(path, &["log", "-1", "--format=%h%x00%cI%x00%s"]) {
+        let mut fields = output.trim_end_matches('\n').splitn(3, '\0');
+        if let (Some(hash), Some(date), Some(subject)) =
+            (fields.next(), fields.next(), fields.next())
+        {
+            status.head_commit = Some(hash.to_string());
+            if let Ok(committed_at) = DateTime::parse_from_rfc3339(date) {
+                status.last_commit = Some(GitCommitSummary {
+                    subject: subject.to_string(),
+                    committed_at: committed_at.with_timezone(&Utc),
+                });
+            }
+        }
+    }
+    status
+}
+
+/// The URL of the `origin` remote, or of the first remote when there is no
+/// `origin`, reduced by [`remote_key`].
+pub fn remote_url(path: &Path) -> Option<String> {
+    let remotes = run_git(path, &["remote"]).ok()?;
+    let remote = remotes
+        .lines()
+        .find(|remote| *remote == "origin")
+        .or_else(|| remotes.lines().next())?;
+    let url = run_git(path, &["remote", "get-url", remote]).ok()?;
+    Some(remote_key(url.trim()))
+}
+
+/// Reduces a remote URL to `host/path`, so that the SSH and HTTPS forms of
+/// the same repository compare equal.
+pub fn remote_key(url: &str) -> String {
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let url = url.split_once('@').map_or(url, |(_, rest)| rest);
+    // `host:owner/repo` in the scp-like syntax
+    let url = match url.split_once(':') {
+        Some((host, path)) if !path.starts_with('/') && !path.starts_with(char::is_numeric) => {
+            format!("{host}/{path}")
+        }
+        _ => url.to_string(),
+    };
+    url.trim_end_matches('/')
+        .trim_end_matches(".git")
+        .to_lowercase()
+}
+
+/// Reads the branch headers and counts the entries of
+/// `git status --porcelain=v2 --branch`.
+fn parse_porcelain(output: &str, status: &mut GitStatus) {
+    for line in output.lines() {
+        if let Some(header) = line.strip_prefix("# ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.head" if value != "(detached)" => status.branch = Some(value.to_string()),
+                "branch.upstream" => status.upstream = Some(value.to_string()),
+                "branch.ab" => {
+                    for count in value.split_whitespace() {
+                        if let Some(ahead) = count.strip_prefix('+') {
+                            status.ahead = ahead.parse().unwrap_or(0);
+                        } else if let Some(behind) = count.strip_prefix('-') {
+                            status.behind = behind.parse().unwrap_or(0);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+        match line.chars().next() {
+            Some('1' | '2' | 'u') => status.dirty += 1,
+            Some('?') => status.untracked += 1,
+            _ => {}
+        }
+    }
+}
+
+enum GitError {
+    NotRepository,
+    Failed(String),
+}
+
+fn run_git(path: &Path, args: &[&str]) -> Result<String, GitError> {
+    let mut command = Command::new("git");
+    command
+        .arg("-C")
+        .arg(path)
+        .arg("--no-optional-locks")
+        .args(args)
+        // Error messages are matched below and must not be translated
+        .env("LC_ALL", "C")
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let output = command
+        .output()
+        .map_err(|err| GitError::Failed(format!("failed to run git: {err}")))?;
+    if output.status.success() {
+        return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if stderr.contains("not a git repository") {
+        Err(GitError::NotRepository)
+    } else {
+        Err(GitError::Failed(stderr))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blank_status() -> GitStatus {
+        GitStatus {
+            kind: GitRepoKind::Worktree,
+            branch: None,
+            head_commit: None,
+            upstream: None,
+            ahead: 0,
+            behind: 0,
+            dirty: 0,
+            untracked: 0,
+            stash_count: 0,
+            last_commit: None,
+            error: None,
+            checked_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn parse_porcelain_reads_branch_headers_and_counts_entries() {
+        let output = "# branch.oid 1f2e3d\n\
+                      # branch.head main\n\
+                      # branch.upstream origin/main\n\
+                      # branch.ab +2 -3\n\
+                      1 .M N... 100644 100644 100644 a b src/lib.rs\n\
+                      2 R. N... 100644 100644 100644 a b R100 new.rs\told.rs\n\
+                      u UU N... 100644 100644 100644 100644 a b c conflict.rs\n\
+                      ? notes.txt\n\
+                      ? tmp/\n\
+                      ! ignored.log\n";
+        let mut status = blank_status();
+        parse_porcelain(output, &mut status);
+        assert_eq!(status.branch.as_deref(), Some("main"));
+        assert_eq!(status.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((status.ahead, status.behind), (2, 3));
+        assert_eq!((status.dirty, status.untracked), (3, 2));
+    }
+
+    #[test]
+    fn parse_porcelain_leaves_detached_head_without_branch() {
+        let mut status = blank_status();
+        parse_porcelain(
+            "# branch.oid 1f2e3d\n# branch.head (detached)\n",
+            &mut status,
+        );
+        assert_eq!(status.branch, None);
+        assert_eq!(status.upstream, None);
+    }
+
+    #[test]
+    fn remote_key_matches_ssh_and_https_forms() {
+        let expected = "github.com/owner/repo";
+        for url in [
+            "https://github.com/Owner/repo.git",
+            "https://user@github.com/owner/repo/",
+            "git@github.com:owner/repo.git",
+            "ssh://git@github.com/owner/repo",
+        ] {
+            assert_eq!(remote_key(url), expected, "{url}");
+        }
+        assert_eq!(
+            remote_key("ssh://git@example.com:2222/owner/repo.git"),
+            "example.com:2222/owner/repo"
+        );
+        assert_eq!(remote_key("/srv/git/repo.git"), "/srv/git/repo");
+    }
+}
@@ -0,0 +1,297 @@
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, AppResult},
+    git,
+    models::{PathHealth, ProjectPathHealth, RelocationCandidate, RelocationMatch},
+    project, scan,
+    state::WorkspaceHandle,
+};
+
+pub const PATH_HEALTH_EVENT: &str = "project-path-health";
+
+/// Directories under which removable drives and network shares are mounted.
+const MOUNT_PARENTS: [&str; 4] = ["/Volumes", "/media", "/run/media", "/mnt"];
+/// Levels below a mount parent that may name the volume, as in
+/// `/media/<user>/<label>`.
+const VOLUME_NAME_DEPTH: usize = 2;
+
+/// Tells an available path apart from a missing one and from one whose
+/// volume is not mounted. The second value is the missing mount point.
+pub fn check(path: &Path) -> (PathHealth, Option<PathBuf>) {
+    match fs::metadata(path) {
+        Ok(metadata) if !metadata.is_dir() => (PathHealth::NotDirectory, None),
+        Ok(_) => match fs::read_dir(path) {
+            Err(err) if err.kind() == io::ErrorKind::PermissionDenied => {
+                (PathHealth::PermissionDenied, None)
+            }
+            _ => (PathHealth::Available, None),
+        },
+        Err(err) if err.kind() == io::ErrorKind::PermissionDenied => {
+            (PathHealth::PermissionDenied, None)
+        }
+        Err(_) => match unavailable_volume(path) {
+            Some(volume) => (PathHealth::VolumeUnavailable, Some(volume)),
+            None => (PathHealth::Missing, None),
+        },
+    }
+}
+
+/// Fails with a launch error that says why the project path cannot be used.
+pub fn ensure_available(path: &Path) -> AppResult<()> {
+    let message = match check(path) {
+        (PathHealth::Available, _) => return Ok(()),
+        (PathHealth::Missing, _) => format!(
+            "project path no longer exists, it may have been moved or deleted: {}",
+            path.display()
+        ),
+        (PathHealth::VolumeUnavailable, volume) => format!(
+            "project path is on a volume that is not mounted ({}): {}",
+            volume.unwrap_or_default().display(),
+            path.display()
+        ),
+        (PathHealth::NotDirectory, _) => {
+            format!("project path is not a directory: {}", path.display())
+        }
+        (PathHealth::PermissionDenied, _) => {
+            format!("permission denied for project path: {}", path.display())
+        }
+    };
+    Err(AppError::Launch(message))
+}
+
+/// The mount point `path` depends on, if that mount point is gone or empty.
+/// A drive letter or share that does not exist counts as well.
+fn unavailable_volume(path: &Path) -> Option<PathBuf> {
+    unavailable_volume_under(path, &MOUNT_PARENTS.map(Path::new))
+}
+
+fn unavailable_volume_under(path: &Path, mount_parents: &[&Path]) -> Option<PathBuf> {
+    let Some(existing) = path.ancestors().find(|ancestor| ancestor.exists()) else {
+        return path.ancestors().last().map(Path::to_path_buf);
+    };
+    let mount_parent = mount_parents
+        .iter()
+        .find(|parent| path.starts_with(parent))?;
+    let depth = existing
+        .strip_prefix(mount_parent)
+        .ok()?
+        .components()
+        .count();
+
+    if depth > 0 {
+        // The volume is there, the project is not
+        if is_mount_point(existing) {
+            return None;
+        }
+        // An unmounted mount point is usually left behind as an empty directory
+        let is_empty = fs::read_dir(existing).is_ok_and(|mut entries| entries.next().is_none());
+        if is_empty {
+            return Some(existing.to_path_buf());
+        }
+    }
+    // Otherwise the volume directory itself was removed on unmount
+    (depth < VOLUME_NAME_DEPTH).then(|| {
+        path.components()
+            .take(mount_parent.components().count() + depth + 1)
+            .collect()
+    })
+}
+
+#[cfg(unix)]
+fn is_mount_point(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    let Some(parent) = path.parent() else {
+        return true;
+    };
+    match (fs::metadata(path), fs::metadata(parent)) {
+        (Ok(dir), Ok(parent)) => dir.dev() != parent.dev(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_mount_point(_path: &Path) -> bool {
+    false
+}
+
+/// Checks the path of every project in the workspace outside the trash.
+pub async fn check_projects(handle: &WorkspaceHandle) -> AppResult<Vec<ProjectPathHealth>> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT id, path FROM projects WHERE deleted_at IS NULL ORDER BY name COLLATE NOCASE",
+    )
+    .fetch_all(&handle.pool)
+    .await?;
+    let projects = rows
+        .into_iter()
+        .map(|(id, path)| {
+            let id = Uuid::parse_str(&id)
+                .map_err(|err| AppError::Validation(format!("invalid project id '{id}': {err}")))?;
+            Ok((id, PathBuf::from(path)))
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+
+    // Stat calls on a stale network mount can block for a long time
+    tokio::task::spawn_blocking(move || {
+        projects
+            .into_iter()
+            .map(|(project_id, path)| {
+                let (health, volume) = check(&path);
+                ProjectPathHealth {
+                    project_id,
+                    path,
+                    health,
+                    volume,
+                    checked_at: Utc::now(),
+                }
+            })
+            .collect()
+    })
+    .await
+    .map_err(|err| AppError::Io(io::Error::other(err)))
+}
+
+/// Remembers the git remote of the available projects, which is what
+/// [`find_relocations`] matches on once a project has gone missing.
+pub async fn record_remotes(
+    handle: &WorkspaceHandle,
+    statuses: &[ProjectPathHealth],
+) -> AppResult<()> {
+    let available: Vec<(Uuid, PathBuf)> = statuses
+        .iter()
+        .filter(|status| status.health == PathHealth::Available)
+        .map(|status| (status.project_id, status.path.clone()))
+        .collect();
+    let remotes = tokio::task::spawn_blocking(move || {
+        available
+            .into_iter()
+            .map(|(id, path)| (id, git::remote_url(&path)))
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|err| AppError::Io(io::Error::other(err)))?;
+
+    for (id, remote) in remotes {
+        sqlx::query("UPDATE projects SET git_remote = ? WHERE id = ?")
+            .bind(remote)
+            .bind(id.to_string())
+            .execute(&handle.pool)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Looks for the directory a missing project was moved to. Candidates are
+/// searched under `extra_roots`, the workspace's watched folders, the parent
+/// directories of its other projects and the project's former parent. Clones
+/// of the same git remote come first, then directories with the same name
+/// and primary language.
+pub async fn find_relocations(
+    handle: &WorkspaceHandle,
+    project_id: Uuid,
+    extra_roots: Vec<PathBuf>,
+) -> AppResult<Vec<RelocationCandidate>> {
+    let project = project::get_project(handle, project_id).await?;
+    let remote = project::git_remote(handle, project_id).await?;
+    let taken = project::project_paths(handle).await?;
+
+    let mut roots = extra_roots;
+    roots.extend(
+        handle
+            .meta
+            .watched_folders
+            .iter()
+            .map(|folder| folder.path.clone()),
+    );
+    roots.extend(
+        taken
+            .keys()
+            .filter_map(|path| Path::new(path).parent().map(Path::to_path_buf)),
+    );
+    roots.extend(project.path.parent().map(Path::to_path_buf));
+
+    let old_name = project
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase());
+    let language = project.stack.primary_language.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let mut seen_roots = HashSet::new();
+        let mut seen = HashSet::new();
+        let mut matches = Vec::new();
+        for root in roots {
+            if !root.is_dir() || !seen_roots.insert(project::path_key(&root)) {
+                continue;
+            }
+            for candidate in scan::find_projects(&root, scan::DEFAULT_SCAN_DEPTH, &[]) {
+                let key = project::path_key(&candidate.path);
+                if taken.contains_key(&key) || !seen.insert(key) {
+                    continue;
+                }
+                let same_remote = remote.is_some()
+                    && candidate.markers.iter().any(|marker| marker == ".git")
+                    && git::remote_url(&candidate.path) == remote;
+                let same_name = old_name.as_deref() == Some(&candidate.name.to_lowercase())
+                    && (language.is_none() || candidate.stack.primary_language == language);
+                let matched_by = if same_remote {
+                    RelocationMatch::GitRemote
+                } else if same_name {
+                    RelocationMatch::Marker
+                } else {
+                    continue;
+                };
+                matches.push(RelocationCandidate {
+                    path: candidate.path,
+                    matched_by,
+                    markers: candidate.markers,
+                });
+            }
+        }
+        matches.sort_by_key(|candidate| candidate.matched_by != RelocationMatch::GitRemote);
+        matches
+    })
+    .await
+    .map_err(|err| AppError::Io(io::Error::other(err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_outside_mount_parents_have_no_volume() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(unavailable_volume(&dir.path().join("missing/app")), None);
+    }
+
+    #[test]
+    fn missing_or_empty_volume_directories_are_reported() {
+        let media = tempfile::tempdir().unwrap();
+        let parents = [media.path()];
+
+        // Removed on unmount
+        let removed = media.path().join("usb");
+        let project = removed.join("projects/app");
+        assert_eq!(unavailable_volume_under(&project, &parents), Some(removed));
+
+        // Left behind as an empty mount point
+        let empty = media.path().join("user/backup");
+        fs::create_dir_all(&empty).unwrap();
+        let project = empty.join("app");
+        assert_eq!(unavailable_volume_under(&project, &parents), Some(empty));
+
+        // The volume is there, only the project is missing
+        let mounted = media.path().join("user/disk");
+        fs::create_dir_all(mounted.join("other")).unwrap();
+        let project = mounted.join("app");
+        assert_eq!(unavailable_volume_under(&project, &parents), None);
+    }
+}
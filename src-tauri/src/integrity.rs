@@ -0,0 +1,448 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    db::{self, DEFAULT_LAUNCH_CONFIG_NAME, SCHEMA_VERSION},
+    error::{AppError, AppResult},
+    health,
+    models::{
+        IntegrityIssue, IntegrityIssueKind, OpenConfig, PathHealth, ProjectStack, RestartPolicy,
+        WorkspaceCheck, WorkspaceRepair,
+    },
+    project::{self, parse_timestamp},
+    search, stack,
+    state::WorkspaceHandle,
+};
+
+/// Child table, referencing column and parent table of every reference
+/// that `ON DELETE CASCADE` should have kept consistent.
+const REFERENCES: [(&str, &str, &str); 6] = [
+    ("launch_configs", "project_id", "projects"),
+    ("launch_history", "project_id", "projects"),
+    ("launch_sessions", "project_id", "projects"),
+    ("project_tags", "project_id", "projects"),
+    ("project_tags", "tag_id", "tags"),
+    ("project_notes", "project_id", "projects"),
+];
+/// Lines of a failed `quick_check` included in the issue.
+const MAX_CORRUPTION_LINES: usize = 5;
+
+pub async fn check_workspace(handle: &WorkspaceHandle) -> AppResult<WorkspaceCheck> {
+    let issues = inspect(handle, false).await?;
+    Ok(WorkspaceCheck {
+        workspace_id: handle.meta.id,
+        schema_version: db::schema_version(&handle.pool).await?,
+        expected_schema_version: SCHEMA_VERSION,
+        issues,
+        checked_at: Utc::now(),
+    })
+}
+
+/// Fixes the issues that can be fixed without losing anything the user
+/// wrote: orphaned rows are deleted, malformed derived columns are reset or
+/// detected again and an outdated schema is migrated. Everything else is
+/// returned as unresolved.
+pub async fn repair_workspace(handle: &WorkspaceHandle) -> AppResult<WorkspaceRepair> {
+    let issues = inspect(handle, true).await?;
+    let (repaired, unresolved): (Vec<_>, Vec<_>) =
+        issues.into_iter().partition(|issue| issue.repairable);
+    if !repaired.is_empty() {
+        search::rebuild_index(&handle.pool).await?;
+    }
+    Ok(WorkspaceRepair {
+        workspace_id: handle.meta.id,
+        repaired,
+        unresolved,
+    })
+}
+
+/// Runs every check in order, applying the fix of each repairable issue as
+/// it is found when `repair` is set. The database may not have opened, so
+/// the row checks only run on an intact file with the current schema.
+async fn inspect(handle: &WorkspaceHandle, repair: bool) -> AppResult<Vec<IntegrityIssue>> {
+    let mut issues = Vec::new();
+    if !check_corruption(handle, &mut issues).await? {
+        return Ok(issues);
+    }
+    if !check_schema_version(handle, repair, &mut issues).await? {
+        return Ok(issues);
+    }
+    check_references(handle, repair, &mut issues).await?;
+    check_project_rows(handle, repair, &mut issues).await?;
+    check_launch_configs(handle, repair, &mut issues).await?;
+    check_default_configs(handle, repair, &mut issues).await?;
+    check_paths(handle, &mut issues).await?;
+    Ok(issues)
+}
+
+/// Whether the database file is intact.
+async fn check_corruption(
+    handle: &WorkspaceHandle,
+    issues: &mut Vec<IntegrityIssue>,
+) -> AppResult<bool> {
+    let report: Vec<String> = sqlx::query_scalar("PRAGMA quick_check")
+        .fetch_all(&handle.pool)
+        .await?;
+    if report != ["ok"] {
+        let details = report
+            .into_iter()
+            .take(MAX_CORRUPTION_LINES)
+            .collect::<Vec<_>>()
+            .join("; ");
+        issues.push(issue(
+            IntegrityIssueKind::Corruption,
+            None,
+            None,
+            format!("database file is damaged: {details}"),
+            false,
+        ));
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// Whether the schema is current, possibly after migrating it. The search
+/// index is left to the repair, as it is built from rows fixed later.
+async fn check_schema_version(
+    handle: &WorkspaceHandle,
+    repair: bool,
+    issues: &mut Vec<IntegrityIssue>,
+) -> AppResult<bool> {
+    let version = db::schema_version(&handle.pool).await?;
+    if version == SCHEMA_VERSION {
+        return Ok(true);
+    }
+    let repairable = version < SCHEMA_VERSION;
+    issues.push(issue(
+        IntegrityIssueKind::SchemaVersion,
+        None,
+        None,
+        format!("schema version is {version}, this build expects {SCHEMA_VERSION}"),
+        repairable,
+    ));
+    if repair && repairable {
+        db::migrate_workspace_schema(&handle.pool, &handle.meta.database_path).await?;
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+async fn check_references(
+    handle: &WorkspaceHandle,
+    repair: bool,
+    issues: &mut Vec<IntegrityIssue>,
+) -> AppResult<()> {
+    for (table, column, parent) in REFERENCES {
+        let condition = format!("{column} NOT IN (SELECT id FROM {parent})");
+        let count: i64 =
+            sqlx::query_scalar(&format!("SELECT count(*) FROM {table} WHERE {condition}"))
+                .fetch_one(&handle.pool)
+                .await?;
+        if count == 0 {
+            continue;
+        }
+        issues.push(issue(
+            IntegrityIssueKind::OrphanedRows,
+            Some(table),
+            None,
+            format!("{count} {table} rows refer to {parent} that no longer exist"),
+            true,
+        ));
+        if repair {
+            sqlx::query(&format!("DELETE FROM {table} WHERE {condition}"))
+                .execute(&handle.pool)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn check_project_rows(
+    handle: &WorkspaceHandle,
+    repair: bool,
+    issues: &mut Vec<IntegrityIssue>,
+) -> AppResult<()> {
+    type Row = (
+        String,
+        String,
+        Option<String>,
+        Option<String>,
+        String,
+        String,
+        Option<String>,
+    );
+    let rows: Vec<Row> = sqlx::query_as(
+        "SELECT id, path, restart_policy, stack, created_at, updated_at, deleted_at FROM projects",
+    )
+    .fetch_all(&handle.pool)
+    .await?;
+
+    for (id, path, restart_policy, detected_stack, created_at, updated_at, deleted_at) in rows {
+        if let Err(err) = Uuid::parse_str(&id) {
+            issues.push(issue(
+                IntegrityIssueKind::UnparseableRow,
+                Some("projects"),
+                Some(&id),
+                format!("project id is not a UUID ({err}), the project cannot be loaded"),
+                false,
+            ));
+        }
+
+        if let Some(Err(err)) = restart_policy
+            .as_deref()
+            .map(serde_json::from_str::<RestartPolicy>)
+        {
+            issues.push(issue(
+                IntegrityIssueKind::UnparseableRow,
+                Some("projects"),
+                Some(&id),
+                format!("restart policy is not valid ({err}), it resets to the default"),
+                true,
+            ));
+            if repair {
+                sqlx::query("UPDATE projects SET restart_policy = NULL WHERE id = ?")
+                    .bind(&id)
+                    .execute(&handle.pool)
+                    .await?;
+            }
+        }
+
+        if let Some(Err(err)) = detected_stack
+            .as_deref()
+            .map(serde_json::from_str::<ProjectStack>)
+        {
+            issues.push(issue(
+                IntegrityIssueKind::UnparseableRow,
+                Some("projects"),
+                Some(&id),
+                format!("detected stack is not valid ({err}), it is detected again"),
+                true,
+            ));
+            if repair {
+                let stack_json = serde_json::to_string(&stack::detect(Path::new(&path)))?;
+                sqlx::query("UPDATE projects SET stack = ? WHERE id = ?")
+                    .bind(stack_json)
+                    .bind(&id)
+                    .execute(&handle.pool)
+                    .await?;
+            }
+        }
+
+        let timestamps = [
+            ("created_at", Some(&created_at)),
+            ("updated_at", Some(&updated_at)),
+            ("deleted_at", deleted_at.as_ref()),
+        ];
+        for (column, value) in timestamps {
+            let Some(Err(err)) = value.map(|value| parse_timestamp(value)) else {
+                continue;
+            };
+            issues.push(issue(
+                IntegrityIssueKind::UnparseableRow,
+                Some("projects"),
+                Some(&id),
+                format!("{column} is not valid ({err}), it is set to the current time"),
+                true,
+            ));
+            if repair {
+                sqlx::query(&format!("UPDATE projects SET {column} = ? WHERE id = ?"))
+                    .bind(Utc::now().to_rfc3339())
+                    .bind(&id)
+                    .execute(&handle.pool)
+                    .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn check_launch_configs(
+    handle: &WorkspaceHandle,
+    repair: bool,
+    issues: &mut Vec<IntegrityIssue>,
+) -> AppResult<()> {
+    let rows: Vec<(String, String, String, String)> =
+        sqlx::query_as("SELECT id, project_id, name, config FROM launch_configs")
+            .fetch_all(&handle.pool)
+            .await?;
+    for (id, project_id, name, config) in rows {
+        if let Err(err) = Uuid::parse_str(&id) {
+            issues.push(issue(
+                IntegrityIssueKind::UnparseableRow,
+                Some("launch_configs"),
+                Some(&id),
+                format!(
+                    "id of launch configuration '{name}' is not a UUID ({err}), it gets a new one"
+                ),
+                true,
+            ));
+            if repair {
+                sqlx::query("UPDATE launch_configs SET id = ? WHERE id = ? AND project_id = ?")
+                    .bind(Uuid::new_v4().to_string())
+                    .bind(&id)
+                    .bind(&project_id)
+                    .execute(&handle.pool)
+                    .await?;
+            }
+        }
+        // Rewriting the configuration would lose what the user set up
+        if let Err(err) = serde_json::from_str::<OpenConfig>(&config) {
+            issues.push(issue(
+                IntegrityIssueKind::UnparseableRow,
+                Some("launch_configs"),
+                Some(&id),
+                format!(
+                    "launch configuration '{name}' is not valid ({err}), edit or delete it in \
+                     the project {project_id}"
+                ),
+                false,
+            ));
+        }
+    }
+    Ok(())
+}
+
+async fn check_default_configs(
+    handle: &WorkspaceHandle,
+    repair: bool,
+    issues: &mut Vec<IntegrityIssue>,
+) -> AppResult<()> {
+    let rows: Vec<(String, String, i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT p.id, p.open_config, count(c.id), coalesce(sum(c.is_default), 0)
+        FROM projects p
+        LEFT JOIN launch_configs c ON c.project_id = p.id
+        GROUP BY p.id
+        HAVING count(c.id) = 0 OR coalesce(sum(c.is_default), 0) != 1
+        "#,
+    )
+    .fetch_all(&handle.pool)
+    .await?;
+    for (project_id, open_config, configs, defaults) in rows {
+        let message = if configs == 0 {
+            "project has no launch configuration, one is created from its open config".into()
+        } else {
+            format!("project has {defaults} default launch configurations, the first one is kept")
+        };
+        issues.push(issue(
+            IntegrityIssueKind::DefaultLaunchConfig,
+            Some("launch_configs"),
+            Some(&project_id),
+            message,
+            true,
+        ));
+        if !repair {
+            continue;
+        }
+        if configs == 0 {
+            let config = serde_json::from_str::<OpenConfig>(&open_config).unwrap_or_default();
+            sqlx::query(
+                r#"
+                INSERT INTO launch_configs (id, project_id, name, position, is_default, config)
+                VALUES (?, ?, ?, 0, 1, ?)
+                "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(&project_id)
+            .bind(DEFAULT_LAUNCH_CONFIG_NAME)
+            .bind(serde_json::to_string(&config)?)
+            .execute(&handle.pool)
+            .await?;
+        } else {
+            let keep: String = sqlx::query_scalar(
+                "SELECT id FROM launch_configs WHERE project_id = ? \
+                 ORDER BY is_default DESC, position LIMIT 1",
+            )
+            .bind(&project_id)
+            .fetch_one(&handle.pool)
+            .await?;
+            sqlx::query("UPDATE launch_configs SET is_default = (id = ?) WHERE project_id = ?")
+                .bind(&keep)
+                .bind(&project_id)
+                .execute(&handle.pool)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Duplicate and missing paths need the user to decide which project to
+/// keep or where it went, so neither is repaired.
+async fn check_paths(handle: &WorkspaceHandle, issues: &mut Vec<IntegrityIssue>) -> AppResult<()> {
+    let rows: Vec<(String, String, String, bool)> = sqlx::query_as(
+        "SELECT id, name, path, deleted_at IS NOT NULL FROM projects ORDER BY name COLLATE NOCASE",
+    )
+    .fetch_all(&handle.pool)
+    .await?;
+    let mut by_path: HashMap<String, Vec<&str>> = HashMap::new();
+    for (_, name, path, _) in &rows {
+        by_path
+            .entry(project::path_key(Path::new(path)))
+            .or_default()
+            .push(name);
+    }
+    for (path, names) in by_path.into_iter().filter(|(_, names)| names.len() > 1) {
+        let names = names
+            .iter()
+            .map(|name| format!("'{name}'"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        issues.push(issue(
+            IntegrityIssueKind::DuplicatePath,
+            Some("projects"),
+            None,
+            format!("projects {names} point at the same directory {path}"),
+            false,
+        ));
+    }
+
+    // Trashed projects are expected to go missing
+    let available: Vec<(String, PathBuf)> = rows
+        .into_iter()
+        .filter(|(.., trashed)| !trashed)
+        .map(|(id, _, path, _)| (id, PathBuf::from(path)))
+        .collect();
+    let missing = tokio::task::spawn_blocking(move || {
+        available
+            .into_iter()
+            .filter(|(_, path)| health::check(path).0 == PathHealth::Missing)
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|err| AppError::Io(io::Error::other(err)))?;
+    for (id, path) in missing {
+        issues.push(issue(
+            IntegrityIssueKind::MissingPath,
+            Some("projects"),
+            Some(&id),
+            format!(
+                "project path no longer exists: {}, relocate or delete the project",
+                path.display()
+            ),
+            false,
+        ));
+    }
+    Ok(())
+}
+
+fn issue(
+    kind: IntegrityIssueKind,
+    table: Option<&str>,
+    row_id: Option<&str>,
+    message: String,
+    repairable: bool,
+) -> IntegrityIssue {
+    IntegrityIssue {
+        kind,
+        table: table.map(str::to_string),
+        row_id: row_id.map(str::to_string),
+        message,
+        repairable,
+    }
+}
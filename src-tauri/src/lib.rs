@@ -1,9 +1,26 @@
 mod commands;
 mod db;
+mod discovery;
 mod error;
+mod export;
+mod frecency;
+mod git;
+mod health;
+mod integrity;
 mod models;
+mod notes;
+mod palette;
+mod placeholders;
+mod presets;
+mod process;
 mod project;
+mod scan;
+mod search;
+mod session;
+mod stack;
 mod state;
+mod tags;
+mod watch;
 
 use std::path::PathBuf;
 
@@ -34,6 +51,10 @@ pub fn run() {
                 .app_data_dir()
                 .map_err(|_| AppError::PathUnavailable(PathBuf::from("app_data_dir")))?;
             let state = tauri::async_runtime::block_on(AppState::initialise(data_dir))?;
+            tauri::async_runtime::spawn(state.clone().run_supervisor(handle.clone()));
+            tauri::async_runtime::spawn(state.clone().run_folder_watcher(handle.clone()));
+            tauri::async_runtime::spawn(state.clone().run_path_monitor(handle.clone()));
+            tauri::async_runtime::spawn(state.clone().run_pool_eviction());
             handle.manage(state);
 
             Ok(())
@@ -45,14 +66,56 @@ pub fn run() {
             commands::rename_workspace,
             commands::delete_workspace,
             commands::set_active_workspace,
+            commands::get_workspace_statuses,
+            commands::reopen_workspace,
+            commands::relocate_workspace,
+            commands::check_workspace,
+            commands::repair_workspace,
+            commands::recreate_workspace_database,
             commands::list_projects,
+            commands::search_projects,
+            commands::get_suggestions,
+            commands::refresh_git_status,
             commands::upsert_project,
             commands::delete_project,
+            commands::list_trash,
+            commands::restore_project,
+            commands::purge_project,
+            commands::empty_trash,
+            commands::scan_for_projects,
+            commands::import_projects,
+            commands::set_project_flags,
+            commands::set_project_tags,
+            commands::list_tags,
+            commands::create_tag,
+            commands::update_tag,
+            commands::merge_tags,
+            commands::delete_tag,
+            commands::list_project_notes,
+            commands::save_project_note,
+            commands::delete_project_note,
+            commands::export_workspace,
+            commands::check_project_paths,
+            commands::find_project_relocations,
+            commands::relocate_project,
+            commands::add_watched_folder,
+            commands::remove_watched_folder,
+            commands::list_project_suggestions,
+            commands::accept_project_suggestions,
+            commands::dismiss_project_suggestions,
+            commands::rescan_project_stack,
+            commands::discover_tasks,
             commands::launch_project,
             commands::stop_project,
             commands::get_running_projects,
+            commands::get_running_processes,
+            commands::get_process_output,
+            commands::get_project_output,
+            commands::list_launch_sessions,
+            commands::get_session_summaries,
             commands::get_settings,
             commands::update_settings,
+            commands::get_preset_usage,
             commands::get_activity_stats
         ])
         .run(tauri::generate_context!())
@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
@@ -14,6 +14,92 @@ pub struct WorkspaceRecord {
     pub database_path: PathBuf,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub watched_folders: Vec<WatchedFolder>,
+}
+
+/// Whether the database of a workspace could be opened.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceStatus {
+    pub workspace_id: Uuid,
+    /// False once the database failed to open.
+    pub available: bool,
+    /// Whether the database is open now; closed ones open on first use.
+    pub open: bool,
+    /// Why the database could not be opened.
+    pub error: Option<String>,
+}
+
+/// What happens to projects that appear in a watched folder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchMode {
+    AutoAdd,
+    #[default]
+    Suggest,
+}
+
+pub fn default_watch_depth() -> usize {
+    2
+}
+
+/// A directory watched for new project roots, at most `max_depth` levels
+/// below it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchedFolder {
+    pub id: Uuid,
+    pub path: PathBuf,
+    #[serde(default)]
+    pub mode: WatchMode,
+    #[serde(default = "default_watch_depth")]
+    pub max_depth: usize,
+    #[serde(default)]
+    pub ignore_globs: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchedFolderInput {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub mode: WatchMode,
+    pub max_depth: Option<usize>,
+    #[serde(default)]
+    pub ignore_globs: Vec<String>,
+}
+
+/// A project root found in a watched folder, waiting to be accepted or
+/// dismissed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSuggestion {
+    pub id: Uuid,
+    pub watch_id: Uuid,
+    pub path: PathBuf,
+    pub name: String,
+    pub markers: Vec<String>,
+    pub detected_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct ProjectSuggestionRow {
+    pub id: String,
+    pub watch_id: String,
+    pub path: String,
+    pub name: String,
+    pub markers: String,
+    pub detected_at: String,
+}
+
+/// Emitted when watched folders added or suggested projects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchedFolderEvent {
+    pub workspace_id: Uuid,
+    pub added: Vec<Project>,
+    pub suggested: Vec<ProjectSuggestion>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,11 +124,120 @@ pub enum OpenConfig {
         executable: PathBuf,
         #[serde(default)]
         args: Vec<String>,
+        #[serde(default)]
+        env: LaunchEnv,
+        /// Working directory relative to the project path. Programs keep the
+        /// app's directory when unset.
+        #[serde(default)]
+        cwd: Option<PathBuf>,
     },
     CustomCommand {
         command: String,
         #[serde(default)]
         args: Vec<String>,
+        #[serde(default)]
+        env: LaunchEnv,
+        /// Working directory relative to the project path. Commands run in
+        /// the project directory when unset.
+        #[serde(default)]
+        cwd: Option<PathBuf>,
+    },
+    /// A command line run through the user's login shell, so pipes, `&&`,
+    /// globs and `source` work.
+    ShellCommand {
+        command: String,
+        #[serde(default)]
+        env: LaunchEnv,
+        #[serde(default)]
+        cwd: Option<PathBuf>,
+    },
+    /// A multi-line script written to a temporary file and run with
+    /// `interpreter`.
+    InlineScript {
+        interpreter: ScriptInterpreter,
+        script: String,
+        #[serde(default)]
+        env: LaunchEnv,
+        #[serde(default)]
+        cwd: Option<PathBuf>,
+    },
+    /// A launch preset from the settings, resolved at launch time so that
+    /// editing the preset updates every project using it.
+    Preset {
+        preset_id: Uuid,
+        #[serde(default)]
+        overrides: PresetOverrides,
+    },
+}
+
+/// Project-specific changes applied on top of a referenced preset.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetOverrides {
+    /// Replaces the preset's arguments when set.
+    #[serde(default)]
+    pub args: Option<Vec<String>>,
+    /// Applied after the preset's own environment changes.
+    #[serde(default)]
+    pub env: Vec<EnvChange>,
+    /// Replaces the preset's working directory when set.
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptInterpreter {
+    Bash,
+    Sh,
+    Python,
+    Node,
+}
+
+impl ScriptInterpreter {
+    pub fn program(&self) -> &'static str {
+        match self {
+            Self::Bash => "bash",
+            Self::Sh => "sh",
+            Self::Python if cfg!(windows) => "python",
+            Self::Python => "python3",
+            Self::Node => "node",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Bash | Self::Sh => "sh",
+            Self::Python => "py",
+            Self::Node => "js",
+        }
+    }
+}
+
+/// Environment of a launched process, built from the app's own environment
+/// (or an empty one when `clear` is set) by applying `vars` in order.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchEnv {
+    #[serde(default)]
+    pub clear: bool,
+    #[serde(default)]
+    pub vars: Vec<EnvChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "op")]
+pub enum EnvChange {
+    Set {
+        name: String,
+        value: String,
+    },
+    Unset {
+        name: String,
+    },
+    /// Adds a directory to the end of `PATH`.
+    AppendPath {
+        entry: String,
     },
 }
 
@@ -52,6 +247,359 @@ impl Default for OpenConfig {
     }
 }
 
+/// Where a discovered task was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskSource {
+    PackageJson,
+    Cargo,
+    Makefile,
+    Justfile,
+    Pyproject,
+    Procfile,
+    Gradle,
+    Maven,
+}
+
+/// A launch configuration suggested from a project's manifests.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredTask {
+    pub name: String,
+    pub source: TaskSource,
+    pub config: OpenConfig,
+}
+
+/// A named way of launching a project. Projects keep an ordered list of
+/// these, exactly one of which is the default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchConfig {
+    pub id: Uuid,
+    pub name: String,
+    pub is_default: bool,
+    pub config: OpenConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchConfigInput {
+    pub id: Option<Uuid>,
+    pub name: String,
+    #[serde(default)]
+    pub is_default: bool,
+    pub config: OpenConfig,
+}
+
+#[derive(Debug, FromRow)]
+pub struct LaunchConfigRow {
+    pub id: String,
+    pub project_id: String,
+    pub name: String,
+    pub is_default: bool,
+    pub config: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartMode {
+    #[default]
+    Never,
+    OnFailure,
+    Always,
+}
+
+fn default_restart_max_retries() -> u32 {
+    5
+}
+
+fn default_restart_window_secs() -> u64 {
+    300
+}
+
+fn default_restart_initial_backoff_ms() -> u64 {
+    1_000
+}
+
+fn default_restart_max_backoff_ms() -> u64 {
+    60_000
+}
+
+/// When a tracked process is restarted after it exits. More than
+/// `max_retries` restarts within `window_secs` counts as a crash loop.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestartPolicy {
+    #[serde(default)]
+    pub mode: RestartMode,
+    #[serde(default = "default_restart_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "default_restart_window_secs")]
+    pub window_secs: u64,
+    #[serde(default = "default_restart_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    #[serde(default = "default_restart_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            mode: RestartMode::Never,
+            max_retries: default_restart_max_retries(),
+            window_secs: default_restart_window_secs(),
+            initial_backoff_ms: default_restart_initial_backoff_ms(),
+            max_backoff_ms: default_restart_max_backoff_ms(),
+        }
+    }
+}
+
+/// What a project is built with, detected from the marker files in its
+/// directory. Names are lowercase, e.g. `rust`, `spring-boot`, `gradle`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectStack {
+    pub primary_language: Option<String>,
+    #[serde(default)]
+    pub languages: Vec<String>,
+    #[serde(default)]
+    pub frameworks: Vec<String>,
+    #[serde(default)]
+    pub build_systems: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+    pub id: Uuid,
+    pub name: String,
+    /// A `#rrggbb` colour.
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagInput {
+    pub name: String,
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagSummary {
+    pub id: Uuid,
+    pub name: String,
+    pub color: Option<String>,
+    pub project_count: i64,
+}
+
+#[derive(Debug, FromRow)]
+pub struct TagRow {
+    pub id: String,
+    pub name: String,
+    pub color: Option<String>,
+}
+
+/// A markdown document attached to a project.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectNote {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub title: String,
+    /// Markdown source.
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectNoteInput {
+    /// Updates the note with this id, creates a note when absent.
+    pub id: Option<Uuid>,
+    pub title: String,
+    pub body: String,
+}
+
+#[derive(Debug, FromRow)]
+pub struct ProjectNoteRow {
+    pub id: String,
+    pub project_id: String,
+    pub title: String,
+    pub body: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Everything a workspace holds, as written by `export_workspace`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceExport {
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub workspace: WorkspaceRecord,
+    pub tags: Vec<TagSummary>,
+    pub projects: Vec<Project>,
+    pub notes: Vec<ProjectNote>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityIssueKind {
+    /// `PRAGMA quick_check` reported damage.
+    Corruption,
+    /// The schema version differs from the one this build writes.
+    SchemaVersion,
+    /// Rows that refer to a project or tag that no longer exists.
+    OrphanedRows,
+    /// A row with a malformed id, timestamp or JSON column.
+    UnparseableRow,
+    /// A project without exactly one default launch configuration.
+    DefaultLaunchConfig,
+    /// Projects whose paths point at the same directory.
+    DuplicatePath,
+    /// A project whose directory no longer exists.
+    MissingPath,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityIssue {
+    pub kind: IntegrityIssueKind,
+    pub table: Option<String>,
+    /// The affected row, when the issue is about a single row.
+    pub row_id: Option<String>,
+    pub message: String,
+    /// Whether `repair_workspace` fixes this issue.
+    pub repairable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceCheck {
+    pub workspace_id: Uuid,
+    pub schema_version: i64,
+    pub expected_schema_version: i64,
+    pub issues: Vec<IntegrityIssue>,
+    pub checked_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceRepair {
+    pub workspace_id: Uuid,
+    pub repaired: Vec<IntegrityIssue>,
+    /// Issues that need a decision, such as which duplicate project to keep.
+    pub unresolved: Vec<IntegrityIssue>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSearchResult {
+    pub project: Project,
+    pub score: f64,
+    /// Indexed fields that matched, e.g. `name` or `tags`.
+    pub matched_fields: Vec<String>,
+}
+
+/// Order of `list_projects`. Pinned projects come first either way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectSort {
+    #[default]
+    Name,
+    /// Most frequently and recently launched first.
+    Frecency,
+}
+
+/// What a command palette entry opens.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SuggestionTarget {
+    Project { project_id: Uuid },
+    LaunchConfig { project_id: Uuid, config_id: Uuid },
+    Preset { preset_id: Uuid },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Suggestion {
+    pub target: SuggestionTarget,
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub score: f64,
+}
+
+/// Narrows `list_projects`. An empty filter matches every project.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectFilter {
+    #[serde(default)]
+    pub tag_ids: Vec<Uuid>,
+    /// Require every tag in `tag_ids` rather than any of them.
+    #[serde(default)]
+    pub match_all_tags: bool,
+    #[serde(default)]
+    pub favorites_only: bool,
+    #[serde(default)]
+    pub pinned_only: bool,
+}
+
+impl ProjectFilter {
+    pub fn matches(&self, project: &Project) -> bool {
+        let has_tag = |id: &Uuid| project.tags.iter().any(|tag| tag.id == *id);
+        let tags_match = if self.tag_ids.is_empty() {
+            true
+        } else if self.match_all_tags {
+            self.tag_ids.iter().all(has_tag)
+        } else {
+            self.tag_ids.iter().any(has_tag)
+        };
+        tags_match
+            && (!self.favorites_only || project.favorite)
+            && (!self.pinned_only || project.pinned)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GitRepoKind {
+    Worktree,
+    Bare,
+    NotRepository,
+    /// The directory is missing or git could not be run.
+    Unavailable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitCommitSummary {
+    pub subject: String,
+    pub committed_at: DateTime<Utc>,
+}
+
+/// Snapshot of a project's repository. Counts are zero and the optional
+/// fields empty when they do not apply, e.g. outside a work tree.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitStatus {
+    pub kind: GitRepoKind,
+    /// `None` when HEAD is detached or not a repository.
+    pub branch: Option<String>,
+    pub head_commit: Option<String>,
+    pub upstream: Option<String>,
+    pub ahead: u32,
+    pub behind: u32,
+    /// Tracked files with staged or unstaged changes, conflicts included.
+    pub dirty: u32,
+    pub untracked: u32,
+    pub stash_count: u32,
+    pub last_commit: Option<GitCommitSummary>,
+    pub error: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Project {
@@ -59,7 +607,30 @@ pub struct Project {
     pub name: String,
     pub path: PathBuf,
     pub description: Option<String>,
+    /// The default entry of `launch_configs`, kept for clients that predate
+    /// multiple launch configurations.
     pub open_config: OpenConfig,
+    pub launch_configs: Vec<LaunchConfig>,
+    /// Overrides `AppSettings::stop_grace_period_secs` for this project.
+    pub stop_grace_period_secs: Option<u64>,
+    pub restart_policy: RestartPolicy,
+    pub stack: ProjectStack,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+    #[serde(default)]
+    pub favorite: bool,
+    /// Pinned projects are listed first.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Launch frecency, see `get_suggestions`.
+    #[serde(default)]
+    pub frecency: i64,
+    /// Filled in by `list_projects`; see [`GitStatus`].
+    #[serde(default)]
+    pub git: Option<GitStatus>,
+    /// When the project was moved to the trash.
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -71,8 +642,99 @@ pub struct ProjectInput {
     pub name: String,
     pub path: PathBuf,
     pub description: Option<String>,
+    /// Used as the only launch configuration when `launch_configs` is empty.
+    /// Without `launch_configs`, an update replaces just the default one.
     #[serde(default)]
-    pub open_config: OpenConfig,
+    pub open_config: Option<OpenConfig>,
+    /// Replaces all launch configurations. Omitted fields below keep their
+    /// stored values on update.
+    #[serde(default)]
+    pub launch_configs: Option<Vec<LaunchConfigInput>>,
+    /// `Some(None)` clears the project's own grace period.
+    #[serde(default, deserialize_with = "present")]
+    pub stop_grace_period_secs: Option<Option<u64>>,
+    #[serde(default)]
+    pub restart_policy: Option<RestartPolicy>,
+}
+
+/// Deserializes a field that is present, even as `null`, to `Some`, so that
+/// updates can tell a cleared value from an omitted one.
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// A project root found by `scan_for_projects`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanCandidate {
+    pub path: PathBuf,
+    pub name: String,
+    /// The files that made the directory count as a project, e.g. `.git`.
+    pub markers: Vec<String>,
+    pub stack: ProjectStack,
+    /// Set when the workspace already has a project at this path.
+    pub existing_project_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathHealth {
+    Available,
+    /// Nothing exists at the path any more; the project was moved or deleted.
+    Missing,
+    /// The path lives on a drive or mount that is not currently attached.
+    VolumeUnavailable,
+    NotDirectory,
+    PermissionDenied,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectPathHealth {
+    pub project_id: Uuid,
+    pub path: PathBuf,
+    pub health: PathHealth,
+    /// The missing mount point when `health` is `volume_unavailable`.
+    pub volume: Option<PathBuf>,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Emitted when the periodic check finds projects whose path health changed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathHealthEvent {
+    pub workspace_id: Uuid,
+    pub projects: Vec<ProjectPathHealth>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelocationMatch {
+    /// The directory is a clone of the same remote repository.
+    GitRemote,
+    /// The directory has the same name and stack as the project.
+    Marker,
+}
+
+/// A directory that the missing project was probably moved to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelocationCandidate {
+    pub path: PathBuf,
+    pub matched_by: RelocationMatch,
+    pub markers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectImport {
+    pub path: PathBuf,
+    /// Defaults to the directory name.
+    pub name: Option<String>,
 }
 
 #[derive(Debug, FromRow)]
@@ -81,7 +743,12 @@ pub struct ProjectRow {
     pub name: String,
     pub path: String,
     pub description: Option<String>,
-    pub open_config: String,
+    pub stop_grace_period_secs: Option<i64>,
+    pub restart_policy: Option<String>,
+    pub stack: Option<String>,
+    pub favorite: bool,
+    pub pinned: bool,
+    pub deleted_at: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -98,6 +765,14 @@ fn default_zoom_level() -> u8 {
     100
 }
 
+fn default_stop_grace_period_secs() -> u64 {
+    5
+}
+
+fn default_trash_retention_days() -> u32 {
+    30
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ThemePreference {
@@ -145,6 +820,11 @@ pub struct AppSettings {
     pub font_size: u8,
     #[serde(default)]
     pub launch_presets: Vec<LaunchPreset>,
+    #[serde(default = "default_stop_grace_period_secs")]
+    pub stop_grace_period_secs: u64,
+    /// Days a deleted project stays in the trash; 0 keeps it until purged.
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u32,
 }
 
 impl Default for AppSettings {
@@ -156,6 +836,8 @@ impl Default for AppSettings {
             font_family: None,
             font_size: default_font_size(),
             launch_presets: Vec::new(),
+            stop_grace_period_secs: default_stop_grace_period_secs(),
+            trash_retention_days: default_trash_retention_days(),
         }
     }
 }
@@ -171,6 +853,183 @@ pub struct AppSettingsUpdate {
     pub font_size: u8,
     #[serde(default)]
     pub launch_presets: Vec<LaunchPresetInput>,
+    /// Keeps the current value when omitted.
+    #[serde(default)]
+    pub stop_grace_period_secs: Option<u64>,
+    /// Keeps the current value when omitted.
+    #[serde(default)]
+    pub trash_retention_days: Option<u32>,
+    /// Copies removed presets into the launch configurations that reference
+    /// them instead of refusing the update.
+    #[serde(default)]
+    pub inline_removed_presets: bool,
+}
+
+/// A launch configuration that references a preset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetReference {
+    pub workspace_id: Uuid,
+    pub workspace_name: String,
+    pub project_id: Uuid,
+    pub project_name: String,
+    pub config_id: Uuid,
+    pub config_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetUsage {
+    pub preset_id: Uuid,
+    pub preset_name: String,
+    pub references: Vec<PresetReference>,
+}
+
+/// Which step of `stop_project` ended the process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopOutcome {
+    AlreadyExited,
+    Terminated,
+    Killed,
+    /// Stopping panicked; the process is no longer tracked and may still run.
+    Failed,
+}
+
+/// One tracked process of a project. A project can run several instances at
+/// once as long as their names differ; restarts keep the instance id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessInstance {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub workspace_id: Uuid,
+    pub name: String,
+    /// The launch configuration this instance was started from.
+    pub config_id: Uuid,
+    pub config: OpenConfig,
+    pub session_id: Uuid,
+    pub pid: u32,
+    pub started_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessStopResult {
+    pub instance_id: Uuid,
+    pub project_id: Uuid,
+    pub name: String,
+    pub outcome: StopOutcome,
+}
+
+/// How a launch session ended, or `Running` while it is still open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    Running,
+    Exited,
+    Crashed,
+    Stopped,
+    SpawnFailed,
+    /// Handed off to the system opener, so the process is not tracked.
+    Detached,
+    /// Still open when the app last shut down.
+    Abandoned,
+}
+
+impl SessionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Exited => "exited",
+            Self::Crashed => "crashed",
+            Self::Stopped => "stopped",
+            Self::SpawnFailed => "spawn_failed",
+            Self::Detached => "detached",
+            Self::Abandoned => "abandoned",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "running" => Some(Self::Running),
+            "exited" => Some(Self::Exited),
+            "crashed" => Some(Self::Crashed),
+            "stopped" => Some(Self::Stopped),
+            "spawn_failed" => Some(Self::SpawnFailed),
+            "detached" => Some(Self::Detached),
+            "abandoned" => Some(Self::Abandoned),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchSession {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub instance_id: Option<Uuid>,
+    pub process_name: Option<String>,
+    pub config_id: Option<Uuid>,
+    pub status: SessionStatus,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub duration_secs: Option<i64>,
+    pub pid: Option<u32>,
+    pub exit_code: Option<i32>,
+    pub signal: Option<i32>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct LaunchSessionRow {
+    pub id: String,
+    pub project_id: String,
+    pub instance_id: Option<String>,
+    pub process_name: Option<String>,
+    pub config_id: Option<String>,
+    pub status: String,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub pid: Option<i64>,
+    pub exit_code: Option<i64>,
+    pub signal: Option<i64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    pub project_id: Uuid,
+    pub name: String,
+    pub total_sessions: i64,
+    pub crashes: i64,
+    pub spawn_failures: i64,
+    pub average_duration_secs: Option<f64>,
+    pub last_started_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessRestartEvent {
+    pub instance_id: Uuid,
+    pub project_id: Uuid,
+    pub name: String,
+    pub attempt: u32,
+    pub delay_ms: u64,
+    pub exit_code: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashLoopEvent {
+    pub instance_id: Uuid,
+    pub project_id: Uuid,
+    pub name: String,
+    pub restarts: u32,
+    pub window_secs: u64,
+    pub exit_code: Option<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -198,3 +1057,40 @@ pub struct ActivityStats {
     pub total_projects: i64,
     pub average_daily_launches: f64,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputLine {
+    pub seq: u64,
+    pub stream: OutputStream,
+    pub text: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessOutput {
+    pub instance_id: Uuid,
+    pub project_id: Uuid,
+    pub name: String,
+    pub lines: Vec<OutputLine>,
+    /// Sequence number to pass as `since` on the next poll.
+    pub next_seq: u64,
+    /// True when lines after `since` were already dropped from the buffer.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessOutputEvent {
+    pub instance_id: Uuid,
+    pub project_id: Uuid,
+    pub line: OutputLine,
+}
@@ -0,0 +1,145 @@
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, AppResult},
+    models::{ProjectNote, ProjectNoteInput, ProjectNoteRow},
+    project::parse_timestamp,
+    search,
+    state::WorkspaceHandle,
+};
+
+const NOTE_SELECT: &str =
+    "SELECT id, project_id, title, body, created_at, updated_at FROM project_notes";
+
+/// Notes of one project, or of every project when `project_id` is `None`,
+/// most recently updated first.
+pub async fn list_notes(
+    handle: &WorkspaceHandle,
+    project_id: Option<Uuid>,
+) -> AppResult<Vec<ProjectNote>> {
+    let rows = sqlx::query_as::<_, ProjectNoteRow>(&format!(
+        "{NOTE_SELECT} WHERE (?1 IS NULL OR project_id = ?1) ORDER BY updated_at DESC"
+    ))
+    .bind(project_id.map(|id| id.to_string()))
+    .fetch_all(&handle.pool)
+    .await?;
+    rows.into_iter().map(row_to_note).collect()
+}
+
+/// Creates a note, or updates the one `payload.id` names. An empty title
+/// falls back to the first line of the body.
+pub async fn save_note(
+    handle: &WorkspaceHandle,
+    project_id: Uuid,
+    payload: ProjectNoteInput,
+) -> AppResult<ProjectNote> {
+    let project_id_str = project_id.to_string();
+    let title = note_title(&payload)?;
+    let now = Utc::now().to_rfc3339();
+
+    let mut tx = handle.pool.begin().await?;
+    let exists: bool = sqlx::query_scalar("SELECT count(*) > 0 FROM projects WHERE id = ?")
+        .bind(&project_id_str)
+        .fetch_one(&mut *tx)
+        .await?;
+    if !exists {
+        return Err(AppError::ProjectNotFound(project_id_str));
+    }
+    let note_id = match payload.id {
+        Some(note_id) => {
+            let affected = sqlx::query(
+                "UPDATE project_notes SET title = ?, body = ?, updated_at = ? \
+                 WHERE id = ? AND project_id = ?",
+            )
+            .bind(&title)
+            .bind(&payload.body)
+            .bind(&now)
+            .bind(note_id.to_string())
+            .bind(&project_id_str)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            if affected == 0 {
+                return Err(note_not_found(note_id));
+            }
+            note_id
+        }
+        None => {
+            let note_id = Uuid::new_v4();
+            sqlx::query(
+                r#"
+                INSERT INTO project_notes (id, project_id, title, body, created_at, updated_at)
+                VALUES (?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(note_id.to_string())
+            .bind(&project_id_str)
+            .bind(&title)
+            .bind(&payload.body)
+            .bind(&now)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+            note_id
+        }
+    };
+    search::index_project(&mut tx, &project_id_str).await?;
+    tx.commit().await?;
+    get_note(handle, note_id).await
+}
+
+pub async fn delete_note(handle: &WorkspaceHandle, note_id: Uuid) -> AppResult<Uuid> {
+    let note = get_note(handle, note_id).await?;
+    let mut tx = handle.pool.begin().await?;
+    sqlx::query("DELETE FROM project_notes WHERE id = ?")
+        .bind(note_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+    search::index_project(&mut tx, &note.project_id.to_string()).await?;
+    tx.commit().await?;
+    Ok(note_id)
+}
+
+async fn get_note(handle: &WorkspaceHandle, note_id: Uuid) -> AppResult<ProjectNote> {
+    let row = sqlx::query_as::<_, ProjectNoteRow>(&format!("{NOTE_SELECT} WHERE id = ?"))
+        .bind(note_id.to_string())
+        .fetch_optional(&handle.pool)
+        .await?
+        .ok_or_else(|| note_not_found(note_id))?;
+    row_to_note(row)
+}
+
+fn note_title(payload: &ProjectNoteInput) -> AppResult<String> {
+    let title = payload.title.trim();
+    if !title.is_empty() {
+        return Ok(title.to_string());
+    }
+    // A markdown heading on the first line makes a good title
+    payload
+        .body
+        .lines()
+        .map(|line| line.trim().trim_start_matches('#').trim())
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| AppError::Validation("note needs a title or a body".into()))
+}
+
+fn row_to_note(row: ProjectNoteRow) -> AppResult<ProjectNote> {
+    let parse_id = |value: &str| {
+        Uuid::parse_str(value)
+            .map_err(|err| AppError::Validation(format!("invalid id '{value}': {err}")))
+    };
+    Ok(ProjectNote {
+        id: parse_id(&row.id)?,
+        project_id: parse_id(&row.project_id)?,
+        title: row.title,
+        body: row.body,
+        created_at: parse_timestamp(&row.created_at)?,
+        updated_at: parse_timestamp(&row.updated_at)?,
+    })
+}
+
+fn note_not_found(note_id: Uuid) -> AppError {
+    AppError::Validation(format!("note not found: {note_id}"))
+}
@@ -0,0 +1,118 @@
+use std::cmp::Ordering;
+
+use crate::{
+    error::AppResult,
+    frecency,
+    models::{LaunchPreset, Suggestion, SuggestionTarget},
+    project, search,
+    state::WorkspaceHandle,
+};
+
+pub const DEFAULT_SUGGESTION_LIMIT: usize = 15;
+const MAX_SUGGESTION_LIMIT: usize = 100;
+/// Launch configurations and presets rank just below a project with the same
+/// score, so that the project itself comes first.
+const SECONDARY_WEIGHT: f64 = 0.9;
+
+/// Ranks projects, launch configurations and presets against `query` for the
+/// command palette. An empty query lists what was used most recently.
+pub async fn get_suggestions(
+    handle: &WorkspaceHandle,
+    presets: &[LaunchPreset],
+    query: &str,
+    limit: Option<usize>,
+) -> AppResult<Vec<Suggestion>> {
+    let terms = search::tokenize(query);
+    let limit = limit
+        .unwrap_or(DEFAULT_SUGGESTION_LIMIT)
+        .clamp(1, MAX_SUGGESTION_LIMIT);
+    // Without a query only what was launched before is worth suggesting
+    let relevance = |text: &str, frecency: i64| {
+        if terms.is_empty() {
+            (frecency > 0).then_some(1.0)
+        } else {
+            search::text_score(&terms, text)
+        }
+    };
+
+    let projects = project::list_projects(handle).await?;
+    let mut suggestions = Vec::new();
+    if terms.is_empty() {
+        for project in &projects {
+            if project.frecency > 0 {
+                suggestions.push(Suggestion {
+                    target: SuggestionTarget::Project {
+                        project_id: project.id,
+                    },
+                    title: project.name.clone(),
+                    subtitle: Some(project.path.to_string_lossy().to_string()),
+                    score: frecency::weigh(1.0, project.frecency),
+                });
+            }
+        }
+    } else {
+        for result in search::search_projects(handle, query, Some(limit)).await? {
+            suggestions.push(Suggestion {
+                target: SuggestionTarget::Project {
+                    project_id: result.project.id,
+                },
+                title: result.project.name,
+                subtitle: Some(result.project.path.to_string_lossy().to_string()),
+                score: result.score,
+            });
+        }
+    }
+
+    let config_scores = frecency::config_scores(&handle.pool).await?;
+    for project in &projects {
+        // A lone configuration is what opening the project launches anyway
+        if project.launch_configs.len() < 2 {
+            continue;
+        }
+        for config in &project.launch_configs {
+            let frecency = config_scores
+                .get(&config.id.to_string())
+                .copied()
+                .unwrap_or(0);
+            let text = format!("{} {}", project.name, config.name);
+            if let Some(relevance) = relevance(&text, frecency) {
+                suggestions.push(Suggestion {
+                    target: SuggestionTarget::LaunchConfig {
+                        project_id: project.id,
+                        config_id: config.id,
+                    },
+                    title: config.name.clone(),
+                    subtitle: Some(project.name.clone()),
+                    score: frecency::weigh(relevance, frecency) * SECONDARY_WEIGHT,
+                });
+            }
+        }
+    }
+
+    let preset_scores = frecency::preset_scores(&handle.pool).await?;
+    for preset in presets {
+        let frecency = preset_scores
+            .get(&preset.id.to_string())
+            .copied()
+            .unwrap_or(0);
+        let text = format!(
+            "{} {}",
+            preset.name,
+            preset.description.as_deref().unwrap_or_default()
+        );
+        if let Some(relevance) = relevance(&text, frecency) {
+            suggestions.push(Suggestion {
+                target: SuggestionTarget::Preset {
+                    preset_id: preset.id,
+                },
+                title: preset.name.clone(),
+                subtitle: preset.description.clone(),
+                score: frecency::weigh(relevance, frecency) * SECONDARY_WEIGHT,
+            });
+        }
+    }
+
+    suggestions.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+    suggestions.truncate(limit);
+    Ok(suggestions)
+}
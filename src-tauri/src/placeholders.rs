@@ -0,0 +1,276 @@
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+
+use crate::{
+    error::{AppError, AppResult},
+    models::{EnvChange, LaunchEnv, OpenConfig, PresetOverrides},
+};
+
+/// Values substituted for `${...}` placeholders when a project is launched.
+/// A literal `${` is written as `$${`. Shell commands only expand the
+/// placeholders below and leave any other `${...}` to the shell.
+pub struct PlaceholderContext<'a> {
+    pub project_path: &'a Path,
+    pub project_name: &'a str,
+    pub workspace_name: &'a str,
+}
+
+enum Placeholder<'a> {
+    ProjectPath,
+    ProjectName,
+    WorkspaceName,
+    Date,
+    Env(&'a str),
+}
+
+/// What [`render`] does with a `${...}` that is not a known placeholder.
+#[derive(Clone, Copy)]
+enum Unknown {
+    Reject,
+    /// Copy it unchanged, for text with `${...}` syntax of its own.
+    Keep,
+}
+
+impl PlaceholderContext<'_> {
+    pub fn expand(&self, value: &str) -> AppResult<String> {
+        self.render(value, Unknown::Reject)
+    }
+
+    /// Expands the command line of a shell command, where `${HOME}` or
+    /// `${PORT:-3000}` are shell parameter expansions.
+    pub fn expand_shell(&self, command: &str) -> AppResult<String> {
+        self.render(command, Unknown::Keep)
+    }
+
+    fn render(&self, value: &str, unknown: Unknown) -> AppResult<String> {
+        render(value, unknown, |placeholder| match placeholder {
+            Placeholder::ProjectPath => self.project_path.to_string_lossy().to_string(),
+            Placeholder::ProjectName => self.project_name.to_string(),
+            Placeholder::WorkspaceName => self.workspace_name.to_string(),
+            Placeholder::Date => Local::now().format("%Y-%m-%d").to_string(),
+            // Unset variables expand to nothing, like in a shell
+            Placeholder::Env(name) => std::env::var(name).unwrap_or_default(),
+        })
+    }
+
+    pub fn expand_path(&self, path: &Path) -> AppResult<PathBuf> {
+        self.expand(&path.to_string_lossy()).map(PathBuf::from)
+    }
+
+    pub fn expand_args(&self, args: &[String]) -> AppResult<Vec<String>> {
+        args.iter().map(|arg| self.expand(arg)).collect()
+    }
+
+    pub fn expand_env(&self, env: &LaunchEnv) -> AppResult<LaunchEnv> {
+        let vars = env
+            .vars
+            .iter()
+            .map(|change| {
+                Ok(match change {
+                    EnvChange::Set { name, value } => EnvChange::Set {
+                        name: name.clone(),
+                        value: self.expand(value)?,
+                    },
+                    EnvChange::Unset { name } => EnvChange::Unset { name: name.clone() },
+                    EnvChange::AppendPath { entry } => EnvChange::AppendPath {
+                        entry: self.expand(entry)?,
+                    },
+                })
+            })
+            .collect::<AppResult<_>>()?;
+        Ok(LaunchEnv {
+            clear: env.clear,
+            vars,
+        })
+    }
+}
+
+/// Checks that every placeholder in `value` is well formed and known.
+pub fn validate(value: &str) -> AppResult<()> {
+    render(value, Unknown::Reject, |_| String::new()).map(|_| ())
+}
+
+/// Validates the placeholders of every field expanded at launch. Inline
+/// script bodies and shell command lines are left alone, since `${...}` is
+/// valid syntax in them.
+pub fn validate_config(config: &OpenConfig) -> AppResult<()> {
+    let (command, args, env, cwd) = match config {
+        OpenConfig::SystemDefault => return Ok(()),
+        OpenConfig::CustomApp {
+            executable,
+            args,
+            env,
+            cwd,
+        } => (
+            Some(executable.to_string_lossy()),
+            args.as_slice(),
+            env.vars.as_slice(),
+            cwd,
+        ),
+        OpenConfig::CustomCommand {
+            command,
+            args,
+            env,
+            cwd,
+        } => (
+            Some(command.into()),
+            args.as_slice(),
+            env.vars.as_slice(),
+            cwd,
+        ),
+        OpenConfig::ShellCommand { env, cwd, .. } => (None, &[][..], env.vars.as_slice(), cwd),
+        OpenConfig::InlineScript { env, cwd, .. } => (None, &[][..], env.vars.as_slice(), cwd),
+        OpenConfig::Preset {
+            overrides: PresetOverrides { args, env, cwd },
+            ..
+        } => (
+            None,
+            args.as_deref().unwrap_or_default(),
+            env.as_slice(),
+            cwd,
+        ),
+    };
+
+    if let Some(command) = command {
+        validate(&command)?;
+    }
+    for arg in args {
+        validate(arg)?;
+    }
+    for change in env {
+        match change {
+            EnvChange::Set { value, .. } => validate(value)?,
+            EnvChange::Unset { .. } => {}
+            EnvChange::AppendPath { entry } => validate(entry)?,
+        }
+    }
+    if let Some(cwd) = cwd {
+        validate(&cwd.to_string_lossy())?;
+    }
+    Ok(())
+}
+
+pub fn has_placeholders(value: &str) -> bool {
+    value.contains("${")
+}
+
+/// Whether any argument mentions `${projectPath}`, in which case programs
+/// no longer get the project path appended.
+pub fn references_project_path(args: &[String]) -> bool {
+    args.iter().any(|arg| {
+        let mut found = false;
+        let _ = render(arg, Unknown::Reject, |placeholder| {
+            found |= matches!(placeholder, Placeholder::ProjectPath);
+            String::new()
+        });
+        found
+    })
+}
+
+fn render<'a>(
+    value: &'a str,
+    unknown: Unknown,
+    mut resolve: impl FnMut(Placeholder<'a>) -> String,
+) -> AppResult<String> {
+    let mut output = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            output.push_str(&rest[..start - 1]);
+            output.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        output.push_str(&rest[..start]);
+
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            if let Unknown::Keep = unknown {
+                output.push_str(&rest[start..]);
+                return Ok(output);
+            }
+            return Err(AppError::Validation(format!(
+                "unterminated placeholder in '{value}'"
+            )));
+        };
+        match (parse_placeholder(&after[..end]), unknown) {
+            (Ok(placeholder), _) => output.push_str(&resolve(placeholder)),
+            (Err(_), Unknown::Keep) => output.push_str(&rest[start..start + end + 3]),
+            (Err(err), Unknown::Reject) => return Err(err),
+        }
+        rest = &after[end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+fn parse_placeholder(name: &str) -> AppResult<Placeholder<'_>> {
+    match name {
+        "projectPath" => Ok(Placeholder::ProjectPath),
+        "projectName" => Ok(Placeholder::ProjectName),
+        "workspaceName" => Ok(Placeholder::WorkspaceName),
+        "date" => Ok(Placeholder::Date),
+        _ => match name.strip_prefix("env:") {
+            Some(var) if !var.is_empty() => Ok(Placeholder::Env(var)),
+            _ => Err(AppError::Validation(format!(
+                "unknown variable '${{{name}}}'; expected projectPath, projectName, \
+                 workspaceName, date or env:NAME (write $${{ for a literal ${{)"
+            ))),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render_with_names(value: &str) -> AppResult<String> {
+        render_names(value, Unknown::Reject)
+    }
+
+    fn render_names(value: &str, unknown: Unknown) -> AppResult<String> {
+        render(value, unknown, |placeholder| match placeholder {
+            Placeholder::ProjectPath => "/work/app".to_string(),
+            Placeholder::ProjectName => "app".to_string(),
+            Placeholder::WorkspaceName => "main".to_string(),
+            Placeholder::Date => "2024-01-31".to_string(),
+            Placeholder::Env(var) => format!("<{var}>"),
+        })
+    }
+
+    #[test]
+    fn render_replaces_placeholders() {
+        let rendered = render_with_names("${projectName} in ${workspaceName} at ${projectPath}");
+        assert_eq!(rendered.unwrap(), "app in main at /work/app");
+        assert_eq!(render_with_names("${env:HOME}/x").unwrap(), "<HOME>/x");
+        assert_eq!(render_with_names("plain text").unwrap(), "plain text");
+    }
+
+    #[test]
+    fn render_keeps_escaped_placeholders() {
+        assert_eq!(
+            render_with_names("$${projectName} is ${projectName}").unwrap(),
+            "${projectName} is app"
+        );
+    }
+
+    #[test]
+    fn render_rejects_malformed_placeholders() {
+        assert!(render_with_names("${projectName").is_err());
+        assert!(render_with_names("${unknown}").is_err());
+        assert!(render_with_names("${env:}").is_err());
+    }
+
+    #[test]
+    fn shell_syntax_is_kept_when_unknown_placeholders_are() {
+        let rendered = render_names(
+            "cd ${projectPath} && echo ${HOME} ${PORT:-3000} $${projectName} '${'",
+            Unknown::Keep,
+        );
+        assert_eq!(
+            rendered.unwrap(),
+            "cd /work/app && echo ${HOME} ${PORT:-3000} ${projectName} '${'"
+        );
+    }
+}
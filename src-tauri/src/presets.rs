@@ -0,0 +1,158 @@
+use sqlx::{Row, SqliteConnection};
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, AppResult},
+    models::{LaunchPreset, OpenConfig, PresetOverrides, PresetReference},
+    state::WorkspaceHandle,
+};
+
+/// Replaces a preset reference with the preset's config and its overrides.
+/// Other configs are returned unchanged.
+pub fn resolve(config: &OpenConfig, presets: &[LaunchPreset]) -> AppResult<OpenConfig> {
+    match config {
+        OpenConfig::Preset {
+            preset_id,
+            overrides,
+        } => {
+            let preset = presets
+                .iter()
+                .find(|preset| preset.id == *preset_id)
+                .ok_or_else(|| {
+                    AppError::Validation(format!("launch preset not found: {preset_id}"))
+                })?;
+            Ok(apply_overrides(&preset.config, overrides))
+        }
+        other => Ok(other.clone()),
+    }
+}
+
+/// Checks that every preset referenced by `configs` exists.
+pub fn check_references<'a>(
+    configs: impl IntoIterator<Item = &'a OpenConfig>,
+    presets: &[LaunchPreset],
+) -> AppResult<()> {
+    for config in configs {
+        resolve(config, presets)?;
+    }
+    Ok(())
+}
+
+fn apply_overrides(config: &OpenConfig, overrides: &PresetOverrides) -> OpenConfig {
+    let mut config = config.clone();
+    match &mut config {
+        OpenConfig::SystemDefault | OpenConfig::Preset { .. } => {}
+        OpenConfig::CustomApp { args, env, cwd, .. }
+        | OpenConfig::CustomCommand { args, env, cwd, .. } => {
+            if let Some(override_args) = &overrides.args {
+                *args = override_args.clone();
+            }
+            env.vars.extend(overrides.env.iter().cloned());
+            if overrides.cwd.is_some() {
+                cwd.clone_from(&overrides.cwd);
+            }
+        }
+        OpenConfig::ShellCommand { env, cwd, .. } | OpenConfig::InlineScript { env, cwd, .. } => {
+            env.vars.extend(overrides.env.iter().cloned());
+            if overrides.cwd.is_some() {
+                cwd.clone_from(&overrides.cwd);
+            }
+        }
+    }
+    config
+}
+
+/// Lists the launch configurations of the workspace that reference a preset,
+/// paired with the referenced preset id.
+pub async fn find_references(handle: &WorkspaceHandle) -> AppResult<Vec<(Uuid, PresetReference)>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT c.id, c.name, c.config, p.id AS project_id, p.name AS project_name
+        FROM launch_configs c
+        JOIN projects p ON p.id = c.project_id
+        WHERE json_extract(c.config, '$.mode') = 'preset'
+        ORDER BY p.name COLLATE NOCASE, c.position
+        "#,
+    )
+    .fetch_all(&handle.pool)
+    .await?;
+
+    let mut references = Vec::with_capacity(rows.len());
+    for row in rows {
+        let config: String = row.get("config");
+        let OpenConfig::Preset { preset_id, .. } = serde_json::from_str(&config)? else {
+            continue;
+        };
+        let config_id: String = row.get("id");
+        let project_id: String = row.get("project_id");
+        references.push((
+            preset_id,
+            PresetReference {
+                workspace_id: handle.meta.id,
+                workspace_name: handle.meta.name.clone(),
+                project_id: parse_id(&project_id)?,
+                project_name: row.get("project_name"),
+                config_id: parse_id(&config_id)?,
+                config_name: row.get("name"),
+            },
+        ));
+    }
+    Ok(references)
+}
+
+/// Copies each of `presets` into every launch configuration of the workspace
+/// that references it, in a single transaction. Returns how many
+/// configurations were rewritten.
+pub async fn inline_presets(handle: &WorkspaceHandle, presets: &[LaunchPreset]) -> AppResult<u64> {
+    let mut tx = handle.pool.begin().await?;
+    let mut rewritten = 0;
+    for preset in presets {
+        rewritten += inline_preset(&mut tx, preset).await?;
+    }
+    tx.commit().await?;
+    Ok(rewritten)
+}
+
+async fn inline_preset(conn: &mut SqliteConnection, preset: &LaunchPreset) -> AppResult<u64> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, project_id, is_default, config
+        FROM launch_configs
+        WHERE json_extract(config, '$.mode') = 'preset'
+          AND json_extract(config, '$.preset_id') = ?
+        "#,
+    )
+    .bind(preset.id.to_string())
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for row in &rows {
+        let config: String = row.get("config");
+        let config: OpenConfig = serde_json::from_str(&config)?;
+        let inlined = serde_json::to_string(&resolve(&config, std::slice::from_ref(preset))?)?;
+
+        let id: String = row.get("id");
+        sqlx::query("UPDATE launch_configs SET config = ? WHERE id = ?")
+            .bind(&inlined)
+            .bind(&id)
+            .execute(&mut *conn)
+            .await?;
+
+        // Keep the legacy column mirroring the default configuration
+        let is_default: bool = row.get("is_default");
+        if is_default {
+            let project_id: String = row.get("project_id");
+            sqlx::query("UPDATE projects SET open_config = ? WHERE id = ?")
+                .bind(&inlined)
+                .bind(&project_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+    }
+    Ok(rows.len() as u64)
+}
+
+fn parse_id(value: &str) -> AppResult<Uuid> {
+    Uuid::parse_str(value)
+        .map_err(|err| AppError::Validation(format!("invalid id '{value}': {err}")))
+}
@@ -0,0 +1,396 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, OpenOptions},
+    io::{BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    process::{Child, Command},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use tauri::{AppHandle, Emitter};
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, AppResult},
+    models::{
+        OutputLine, OutputStream, ProcessInstance, ProcessOutput, ProcessOutputEvent,
+        RestartPolicy, StopOutcome,
+    },
+};
+
+pub const OUTPUT_EVENT: &str = "process-output";
+
+const OUTPUT_BUFFER_LINES: usize = 2000;
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug)]
+pub struct RunningProcess {
+    pub child: Child,
+    pub instance: ProcessInstance,
+    pub grace_period: Duration,
+    pub restart_policy: RestartPolicy,
+    /// Kept alive until the process is no longer tracked.
+    pub script: Option<TempScript>,
+}
+
+/// Temporary file holding an inline script, removed when dropped.
+#[derive(Debug)]
+pub struct TempScript {
+    path: PathBuf,
+}
+
+impl TempScript {
+    /// Creates the file readable by the current user only, as scripts may
+    /// hold credentials and the temp dir is shared.
+    pub fn write(contents: &str, extension: &str) -> AppResult<Self> {
+        let path = std::env::temp_dir().join(format!("pro-manager-{}.{extension}", Uuid::new_v4()));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&path)?;
+        // Owning the path from here on removes a partially written file
+        let script = Self { path };
+        file.write_all(contents.as_bytes())?;
+        Ok(script)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempScript {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_file(&self.path) {
+            log::warn!(
+                "failed to remove script file {}: {err}",
+                self.path.display()
+            );
+        }
+    }
+}
+
+pub type SharedOutput = Arc<Mutex<OutputBuffer>>;
+
+/// Ring buffer holding the most recent output lines of a process instance.
+#[derive(Debug)]
+pub struct OutputBuffer {
+    project_id: Uuid,
+    name: String,
+    lines: VecDeque<OutputLine>,
+    next_seq: u64,
+    /// Sequence number of the first line of the current launch.
+    run_start: u64,
+    capacity: usize,
+}
+
+impl OutputBuffer {
+    pub fn shared(instance: &ProcessInstance) -> SharedOutput {
+        Arc::new(Mutex::new(Self {
+            project_id: instance.project_id,
+            name: instance.name.clone(),
+            lines: VecDeque::with_capacity(OUTPUT_BUFFER_LINES),
+            next_seq: 0,
+            run_start: 0,
+            capacity: OUTPUT_BUFFER_LINES,
+        }))
+    }
+
+    pub fn belongs_to(&self, project_id: Uuid, name: &str) -> bool {
+        self.project_id == project_id && self.name == name
+    }
+
+    pub fn project_id(&self) -> Uuid {
+        self.project_id
+    }
+
+    /// Drops buffered lines but keeps the sequence counter, so clients polling
+    /// with `since` never see numbers go backwards across relaunches.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.run_start = self.next_seq;
+    }
+
+    pub fn push(&mut self, stream: OutputStream, text: String) -> OutputLine {
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+        }
+        let line = OutputLine {
+            seq: self.next_seq,
+            stream,
+            text,
+            timestamp: Utc::now(),
+        };
+        self.next_seq += 1;
+        self.lines.push_back(line.clone());
+        line
+    }
+
+    /// Lines numbered `since` or later. Lines of earlier launches were cleared
+    /// on purpose, so only lines of the current one count as truncated.
+    pub fn snapshot(&self, instance_id: Uuid, since: Option<u64>) -> ProcessOutput {
+        let since = since.unwrap_or(0);
+        let oldest = self
+            .lines
+            .front()
+            .map(|line| line.seq)
+            .unwrap_or(self.next_seq);
+        let lines = self
+            .lines
+            .iter()
+            .filter(|line| line.seq >= since)
+            .cloned()
+            .collect();
+        ProcessOutput {
+            instance_id,
+            project_id: self.project_id,
+            name: self.name.clone(),
+            lines,
+            next_seq: self.next_seq,
+            truncated: since.max(self.run_start) < oldest,
+        }
+    }
+}
+
+pub fn lock_output(buffer: &SharedOutput) -> MutexGuard<'_, OutputBuffer> {
+    buffer.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Takes the piped stdout/stderr of `child` and drains them on background
+/// threads into `buffer`, emitting every line as an [`OUTPUT_EVENT`].
+pub fn capture_output(
+    app: &AppHandle,
+    instance: &ProcessInstance,
+    child: &mut Child,
+    buffer: &SharedOutput,
+) {
+    let ids = (instance.id, instance.project_id);
+    if let Some(stdout) = child.stdout.take() {
+        spawn_reader(
+            app.clone(),
+            ids,
+            stdout,
+            OutputStream::Stdout,
+            buffer.clone(),
+        );
+    }
+    if let Some(stderr) = child.stderr.take() {
+        spawn_reader(
+            app.clone(),
+            ids,
+            stderr,
+            OutputStream::Stderr,
+            buffer.clone(),
+        );
+    }
+}
+
+fn spawn_reader<R>(
+    app: AppHandle,
+    (instance_id, project_id): (Uuid, Uuid),
+    source: R,
+    stream: OutputStream,
+    buffer: SharedOutput,
+) where
+    R: Read + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut reader = BufReader::new(source);
+        let mut raw = Vec::new();
+        loop {
+            raw.clear();
+            match reader.read_until(b'\n', &mut raw) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(err) => {
+                    log::warn!("failed to read output of process {instance_id}: {err}");
+                    break;
+                }
+            }
+
+            // Dev servers are not guaranteed to print valid UTF-8
+            let text = String::from_utf8_lossy(&raw)
+                .trim_end_matches(['\n', '\r'])
+                .to_string();
+            let line = lock_output(&buffer).push(stream, text);
+
+            let event = ProcessOutputEvent {
+                instance_id,
+                project_id,
+                line,
+            };
+            if let Err(err) = app.emit(OUTPUT_EVENT, event) {
+                log::warn!("failed to emit output event: {err}");
+            }
+        }
+    });
+}
+
+/// Starts the command as the leader of a new process group so that stopping it
+/// also reaches the grandchildren spawned by wrappers like `npm run dev`.
+pub fn isolate_process_group(command: &mut Command) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        command.creation_flags(CREATE_NEW_PROCESS_GROUP);
+    }
+}
+
+/// Asks the process group to terminate, waits up to `grace_period` for every
+/// process in it to exit and kills the whole group if any is still alive
+/// afterwards. A leader that exits early does not end the wait, as wrappers
+/// like shells often die on SIGTERM while their children ignore it.
+pub async fn terminate(child: &mut Child, grace_period: Duration) -> AppResult<StopOutcome> {
+    if !group_alive(child)? {
+        return Ok(StopOutcome::AlreadyExited);
+    }
+
+    signal_group(child, false)?;
+    let deadline = Instant::now() + grace_period;
+    loop {
+        if !group_alive(child)? {
+            return Ok(StopOutcome::Terminated);
+        }
+        if Instant::now() >= deadline {
+            break;
+        }
+        tokio::time::sleep(STOP_POLL_INTERVAL).await;
+    }
+
+    signal_group(child, true)?;
+    child
+        .wait()
+        .map_err(|err| AppError::Launch(format!("failed to reap process: {err}")))?;
+    Ok(StopOutcome::Killed)
+}
+
+fn has_exited(child: &mut Child) -> AppResult<bool> {
+    child
+        .try_wait()
+        .map(|status| status.is_some())
+        .map_err(|err| AppError::Launch(format!("failed to query process: {err}")))
+}
+
+/// Whether any process of the child's group is still running. The leader is
+/// reaped first, since a zombie still counts as a member of its group.
+#[cfg(unix)]
+fn group_alive(child: &mut Child) -> AppResult<bool> {
+    has_exited(child)?;
+    let pgid = child.id() as libc::pid_t;
+    if unsafe { libc::kill(-pgid, 0) } == 0 {
+        return Ok(true);
+    }
+    let err = std::io::Error::last_os_error();
+    match err.raw_os_error() {
+        Some(libc::ESRCH) => Ok(false),
+        // The group exists but a member belongs to someone else
+        Some(libc::EPERM) => Ok(true),
+        _ => Err(AppError::Launch(format!(
+            "failed to query process group: {err}"
+        ))),
+    }
+}
+
+#[cfg(not(unix))]
+fn group_alive(child: &mut Child) -> AppResult<bool> {
+    Ok(!has_exited(child)?)
+}
+
+#[cfg(unix)]
+fn signal_group(child: &mut Child, force: bool) -> AppResult<()> {
+    let signal = if force { libc::SIGKILL } else { libc::SIGTERM };
+    // The child leads its own group, so its pid doubles as the group id
+    let pgid = child.id() as libc::pid_t;
+    if unsafe { libc::kill(-pgid, signal) } == 0 {
+        return Ok(());
+    }
+    let err = std::io::Error::last_os_error();
+    if err.raw_os_error() == Some(libc::ESRCH) {
+        return Ok(());
+    }
+    Err(AppError::Launch(format!(
+        "failed to signal process group: {err}"
+    )))
+}
+
+#[cfg(windows)]
+fn signal_group(child: &mut Child, force: bool) -> AppResult<()> {
+    let mut command = Command::new("taskkill");
+    command.arg("/T").arg("/PID").arg(child.id().to_string());
+    if force {
+        command.arg("/F");
+    }
+    let output = command
+        .output()
+        .map_err(|err| AppError::Launch(format!("failed to signal process tree: {err}")))?;
+    // taskkill also fails for a process that exited in the meantime
+    if output.status.success() || has_exited(child)? {
+        return Ok(());
+    }
+    Err(AppError::Launch(format!(
+        "failed to signal process tree: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+    )))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn signal_group(_child: &mut Child, _force: bool) -> AppResult<()> {
+    Err(AppError::Launch(
+        "stopping processes is not supported on this platform".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer(capacity: usize) -> OutputBuffer {
+        OutputBuffer {
+            project_id: Uuid::new_v4(),
+            name: "dev".to_string(),
+            lines: VecDeque::with_capacity(capacity),
+            next_seq: 0,
+            run_start: 0,
+            capacity,
+        }
+    }
+
+    #[test]
+    fn snapshot_reports_lines_dropped_from_the_current_launch() {
+        let mut output = buffer(2);
+        for text in ["a", "b", "c"] {
+            output.push(OutputStream::Stdout, text.to_string());
+        }
+        let snapshot = output.snapshot(Uuid::nil(), None);
+        assert!(snapshot.truncated);
+        assert_eq!(snapshot.lines.len(), 2);
+        assert!(!output.snapshot(Uuid::nil(), Some(1)).truncated);
+    }
+
+    #[test]
+    fn snapshot_is_not_truncated_after_a_relaunch() {
+        let mut output = buffer(4);
+        output.push(OutputStream::Stdout, "old".to_string());
+        output.clear();
+        assert!(!output.snapshot(Uuid::nil(), Some(0)).truncated);
+
+        output.push(OutputStream::Stderr, "new".to_string());
+        let snapshot = output.snapshot(Uuid::nil(), Some(0));
+        assert!(!snapshot.truncated);
+        assert_eq!(snapshot.lines[0].seq, 1);
+        assert_eq!(snapshot.next_seq, 2);
+    }
+}
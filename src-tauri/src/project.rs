@@ -1,23 +1,46 @@
 use std::{
-    path::{Path, PathBuf},
-    process::Command,
+    collections::{HashMap, HashSet},
+    path::{Component, Path, PathBuf},
+    process::{Command, Stdio},
+    time::Duration,
 };
 
 use chrono::{DateTime, Utc};
-use sqlx::{Row, SqlitePool};
+use sqlx::{Row, SqliteConnection, SqliteExecutor, SqlitePool};
 use uuid::Uuid;
 
 use crate::{
     error::{AppError, AppResult},
-    models::{ActivityPoint, ActivityStats, OpenConfig, Project, ProjectInput, ProjectRow},
+    db::DEFAULT_LAUNCH_CONFIG_NAME,
+    frecency, git, health,
+    models::{
+        ActivityPoint, ActivityStats, EnvChange, LaunchConfig, LaunchConfigInput, LaunchConfigRow,
+        LaunchEnv, OpenConfig, Project, ProjectImport, ProjectInput, ProjectRow, ProjectStack,
+        RestartMode, RestartPolicy, ScriptInterpreter, StopOutcome, Tag,
+    },
+    placeholders::{self, PlaceholderContext},
+    process::{self, TempScript},
+    search,
+    session::{self, SessionTarget},
+    stack,
     state::WorkspaceHandle,
+    tags,
 };
 
+pub(crate) const MAX_STOP_GRACE_PERIOD_SECS: u64 = 300;
+pub(crate) const MAX_TRASH_RETENTION_DAYS: u32 = 3650;
+
 const PROJECT_SELECT: &str = r#"
-SELECT id, name, path, description, open_config, created_at, updated_at
+SELECT id, name, path, description, stop_grace_period_secs, restart_policy, stack, favorite,
+       pinned, deleted_at, created_at, updated_at
 FROM projects
 "#;
 
+const LAUNCH_CONFIG_SELECT: &str = r#"
+SELECT id, project_id, name, is_default, config
+FROM launch_configs
+"#;
+
 pub fn sanitize_path_buf(path: PathBuf) -> PathBuf {
     let s = path.to_string_lossy();
     // Remove U+202A (Left-To-Right Embedding) and U+202C (Pop Directional Formatting)
@@ -29,21 +52,97 @@ pub fn sanitize_path_buf(path: PathBuf) -> PathBuf {
     path
 }
 
+/// Lists the projects that are not in the trash.
 pub async fn list_projects(handle: &WorkspaceHandle) -> AppResult<Vec<Project>> {
-    let rows =
-        sqlx::query_as::<_, ProjectRow>(&format!("{PROJECT_SELECT} ORDER BY name COLLATE NOCASE"))
-            .fetch_all(&handle.pool)
-            .await?;
-    rows.into_iter().map(row_to_project).collect()
+    load_projects(
+        handle,
+        "WHERE deleted_at IS NULL ORDER BY pinned DESC, name COLLATE NOCASE",
+    )
+    .await
 }
 
-pub async fn upsert_project(handle: &WorkspaceHandle, mut payload: ProjectInput) -> AppResult<Project> {
-    // Sanitize paths
-    payload.path = sanitize_path_buf(payload.path);
-    if let OpenConfig::CustomApp { executable, .. } = &mut payload.open_config {
-        *executable = sanitize_path_buf(executable.clone());
+/// Loads the given projects, in no particular order, skipping unknown ids.
+pub async fn get_projects(
+    handle: &WorkspaceHandle,
+    project_ids: &[Uuid],
+) -> AppResult<Vec<Project>> {
+    if project_ids.is_empty() {
+        return Ok(Vec::new());
     }
+    // Formatted uuids are safe to inline
+    let ids = project_ids
+        .iter()
+        .map(|id| format!("'{id}'"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    load_projects(handle, &format!("WHERE id IN ({ids})")).await
+}
 
+/// Lists the projects in the trash, most recently deleted first.
+pub async fn list_trash(handle: &WorkspaceHandle) -> AppResult<Vec<Project>> {
+    load_projects(
+        handle,
+        "WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+    )
+    .await
+}
+
+async fn load_projects(handle: &WorkspaceHandle, clauses: &str) -> AppResult<Vec<Project>> {
+    let rows = sqlx::query_as::<_, ProjectRow>(&format!("{PROJECT_SELECT} {clauses}"))
+        .fetch_all(&handle.pool)
+        .await?;
+    let mut launch_configs = load_launch_configs(&handle.pool, None).await?;
+    let mut tags = tags::load_tags(&handle.pool, None).await?;
+    let frecency = frecency::project_scores(&handle.pool, None).await?;
+    rows.into_iter()
+        .map(|row| {
+            let configs = launch_configs.remove(&row.id).unwrap_or_default();
+            let tags = tags.remove(&row.id).unwrap_or_default();
+            let frecency = frecency.get(&row.id).copied().unwrap_or(0);
+            row_to_project(row, configs, tags, frecency)
+        })
+        .collect()
+}
+
+pub async fn upsert_project(
+    handle: &WorkspaceHandle,
+    mut payload: ProjectInput,
+) -> AppResult<Project> {
+    payload.path = sanitize_path_buf(payload.path);
+    let detected = detect_contents(vec![payload.path.clone()]).await?.remove(0);
+    let mut tx = handle.pool.begin().await?;
+    let project_id = save_project(&mut tx, payload, detected).await?;
+    tx.commit().await?;
+
+    fetch_project(&handle.pool, &project_id).await
+}
+
+/// What is detected from the contents of a project directory: its stack
+/// and git remote.
+type DetectedContents = (ProjectStack, Option<String>);
+
+/// Detects the contents of each of `paths` off the async runtime, since it
+/// reads manifests and waits for git. Callers do this before beginning a
+/// transaction so the write lock is not held meanwhile.
+async fn detect_contents(paths: Vec<PathBuf>) -> AppResult<Vec<DetectedContents>> {
+    tokio::task::spawn_blocking(move || {
+        paths
+            .iter()
+            .map(|path| (stack::detect(path), git::remote_url(path)))
+            .collect()
+    })
+    .await
+    .map_err(|err| AppError::Io(std::io::Error::other(err)))
+}
+
+/// Validates `payload`, whose path is already sanitized, and writes it with
+/// its launch configurations and `detected` contents, leaving the commit to
+/// the caller.
+async fn save_project(
+    conn: &mut SqliteConnection,
+    payload: ProjectInput,
+    (stack, git_remote): DetectedContents,
+) -> AppResult<Uuid> {
     if payload.name.trim().is_empty() {
         return Err(AppError::Validation("project name cannot be empty".into()));
     }
@@ -55,30 +154,81 @@ pub async fn upsert_project(handle: &WorkspaceHandle, mut payload: ProjectInput)
         )));
     }
 
-    validate_open_config(&payload.open_config)?;
+    // Fields an update omits keep their stored values
+    let stored = match payload.id {
+        Some(id) => Some(load_stored_project(conn, id).await?),
+        None => None,
+    };
+    let launch_configs = match (payload.launch_configs, stored.as_ref()) {
+        (Some(inputs), _) => {
+            prepare_launch_configs(inputs, payload.open_config.unwrap_or_default())?
+        }
+        (None, Some(stored)) if !stored.launch_configs.is_empty() => prepare_launch_configs(
+            stored_launch_configs(stored, payload.open_config),
+            OpenConfig::default(),
+        )?,
+        (None, _) => prepare_launch_configs(Vec::new(), payload.open_config.unwrap_or_default())?,
+    };
+    let stop_grace_period_secs = match payload.stop_grace_period_secs {
+        Some(secs) => secs,
+        None => stored
+            .as_ref()
+            .and_then(|stored| stored.stop_grace_period_secs),
+    };
+    let restart_policy = match payload.restart_policy {
+        Some(policy) => policy,
+        None => stored
+            .map(|stored| stored.restart_policy)
+            .unwrap_or_default(),
+    };
+    validate_stop_grace_period(stop_grace_period_secs)?;
+    validate_restart_policy(&restart_policy)?;
 
-    let open_config_json = serde_json::to_string(&payload.open_config)?;
+    let default_config = launch_configs
+        .iter()
+        .find(|config| config.is_default)
+        .expect("prepare_launch_configs marks a default");
+    let open_config_json = serde_json::to_string(&default_config.config)?;
+    let restart_policy_json = serde_json::to_string(&restart_policy)?;
+    let stack_json = serde_json::to_string(&stack)?;
     let path_str = normalise_path(payload.path);
+    let grace_period = stop_grace_period_secs.map(|secs| secs as i64);
     let now = Utc::now().to_rfc3339();
 
-    let pool = &handle.pool;
+    let trashed: Option<String> = sqlx::query_scalar(
+        "SELECT name FROM projects WHERE path = ? AND id != ? AND deleted_at IS NOT NULL",
+    )
+    .bind(&path_str)
+    .bind(payload.id.map(|id| id.to_string()).unwrap_or_default())
+    .fetch_optional(&mut *conn)
+    .await?;
+    if let Some(name) = trashed {
+        return Err(AppError::Validation(format!(
+            "project '{name}' at {path_str} is in the trash, restore or purge it first"
+        )));
+    }
 
     let project_id = if let Some(id) = payload.id {
         let id_str = id.to_string();
         let affected = sqlx::query(
             r#"
         UPDATE projects
-        SET name = ?, path = ?, description = ?, open_config = ?, updated_at = ?
-        WHERE id = ?
+        SET name = ?, path = ?, description = ?, open_config = ?, stop_grace_period_secs = ?,
+            restart_policy = ?, stack = ?, git_remote = ?, updated_at = ?
+        WHERE id = ? AND deleted_at IS NULL
       "#,
         )
         .bind(&payload.name)
         .bind(&path_str)
         .bind(payload.description.as_deref())
         .bind(&open_config_json)
+        .bind(grace_period)
+        .bind(&restart_policy_json)
+        .bind(&stack_json)
+        .bind(&git_remote)
         .bind(&now)
         .bind(&id_str)
-        .execute(pool)
+        .execute(&mut *conn)
         .await?
         .rows_affected();
 
@@ -92,8 +242,11 @@ pub async fn upsert_project(handle: &WorkspaceHandle, mut payload: ProjectInput)
         let created_at = now.clone();
         sqlx::query(
             r#"
-        INSERT INTO projects (id, name, path, description, open_config, created_at, updated_at)
-        VALUES (?, ?, ?, ?, ?, ?, ?)
+        INSERT INTO projects (
+          id, name, path, description, open_config, stop_grace_period_secs, restart_policy,
+          stack, git_remote, created_at, updated_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
       "#,
         )
         .bind(&id_str)
@@ -101,66 +254,544 @@ pub async fn upsert_project(handle: &WorkspaceHandle, mut payload: ProjectInput)
         .bind(&path_str)
         .bind(payload.description.as_deref())
         .bind(&open_config_json)
+        .bind(grace_period)
+        .bind(&restart_policy_json)
+        .bind(&stack_json)
+        .bind(&git_remote)
         .bind(&created_at)
         .bind(&now)
-        .execute(pool)
+        .execute(&mut *conn)
         .await?;
         id
     };
 
-    fetch_project(pool, &project_id).await
+    let project_id_str = project_id.to_string();
+    sqlx::query("DELETE FROM launch_configs WHERE project_id = ?")
+        .bind(&project_id_str)
+        .execute(&mut *conn)
+        .await?;
+    for (position, config) in launch_configs.iter().enumerate() {
+        sqlx::query(
+            r#"
+        INSERT INTO launch_configs (id, project_id, name, position, is_default, config)
+        VALUES (?, ?, ?, ?, ?, ?)
+      "#,
+        )
+        .bind(config.id.to_string())
+        .bind(&project_id_str)
+        .bind(&config.name)
+        .bind(position as i64)
+        .bind(config.is_default)
+        .bind(serde_json::to_string(&config.config)?)
+        .execute(&mut *conn)
+        .await?;
+    }
+    search::index_project(conn, &project_id_str).await?;
+    Ok(project_id)
+}
+
+/// Adds the given directories as projects in a single transaction. Paths the
+/// workspace already has are skipped, so importing twice is harmless.
+pub async fn import_projects(
+    handle: &WorkspaceHandle,
+    imports: Vec<ProjectImport>,
+) -> AppResult<Vec<Project>> {
+    let known = project_paths(handle).await?;
+    let mut keys = HashSet::new();
+    let mut payloads = Vec::with_capacity(imports.len());
+    for import in imports {
+        let path = sanitize_path_buf(import.path);
+        let key = path_key(&path);
+        if known.contains_key(&key) || !keys.insert(key) {
+            continue;
+        }
+
+        let name = import
+            .name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .or_else(|| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+            })
+            .unwrap_or_else(|| normalise_path(path.clone()));
+        payloads.push(ProjectInput {
+            id: None,
+            name,
+            path,
+            description: None,
+            open_config: None,
+            launch_configs: None,
+            stop_grace_period_secs: None,
+            restart_policy: None,
+        });
+    }
+
+    let paths = payloads
+        .iter()
+        .map(|payload| payload.path.clone())
+        .collect();
+    let detected = detect_contents(paths).await?;
+    let mut tx = handle.pool.begin().await?;
+    let mut imported = Vec::with_capacity(payloads.len());
+    for (payload, detected) in payloads.into_iter().zip(detected) {
+        imported.push(save_project(&mut tx, payload, detected).await?);
+    }
+    tx.commit().await?;
+
+    let mut projects = Vec::with_capacity(imported.len());
+    for id in imported {
+        projects.push(fetch_project(&handle.pool, &id).await?);
+    }
+    Ok(projects)
+}
+
+/// Maps the [`path_key`] of every project in the workspace, trashed ones
+/// included, to its id.
+pub async fn project_paths(handle: &WorkspaceHandle) -> AppResult<HashMap<String, Uuid>> {
+    let rows: Vec<(String, String)> = sqlx::query_as("SELECT id, path FROM projects")
+        .fetch_all(&handle.pool)
+        .await?;
+    rows.into_iter()
+        .map(|(id, path)| {
+            let id = Uuid::parse_str(&id)
+                .map_err(|err| AppError::Validation(format!("invalid project id '{id}': {err}")))?;
+            Ok((path_key(Path::new(&path)), id))
+        })
+        .collect()
+}
+
+/// Normalises a project path for comparisons: no trailing separator and,
+/// on case-insensitive Windows paths, lowercase.
+pub(crate) fn path_key(path: &Path) -> String {
+    let path = normalise_path(path.to_path_buf());
+    let trimmed = path.trim_end_matches(['/', '\\']);
+    // Keep the root directory as it is
+    let key = if trimmed.is_empty() {
+        path.as_str()
+    } else {
+        trimmed
+    };
+    if cfg!(windows) {
+        key.to_lowercase()
+    } else {
+        key.to_string()
+    }
 }
 
+/// Moves a project to the trash. It keeps its launch configurations, tags
+/// and notes until it is purged.
 pub async fn delete_project(handle: &WorkspaceHandle, project_id: Uuid) -> AppResult<Uuid> {
     let id_str = project_id.to_string();
-    let affected = sqlx::query("DELETE FROM projects WHERE id = ?")
-        .bind(&id_str)
-        .execute(&handle.pool)
-        .await?
-        .rows_affected();
+    let mut tx = handle.pool.begin().await?;
+    let affected =
+        sqlx::query("UPDATE projects SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL")
+            .bind(Utc::now().to_rfc3339())
+            .bind(&id_str)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
     if affected == 0 {
         return Err(AppError::ProjectNotFound(id_str));
     }
+    search::remove_project(&mut tx, &id_str).await?;
+    tx.commit().await?;
+    Ok(project_id)
+}
+
+pub async fn restore_project(handle: &WorkspaceHandle, project_id: Uuid) -> AppResult<Project> {
+    let id_str = project_id.to_string();
+    let mut tx = handle.pool.begin().await?;
+    let affected = sqlx::query(
+        "UPDATE projects SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+    )
+    .bind(&id_str)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    if affected == 0 {
+        return Err(not_in_trash(&id_str));
+    }
+    search::index_project(&mut tx, &id_str).await?;
+    tx.commit().await?;
+    fetch_project(&handle.pool, &project_id).await
+}
+
+/// Permanently deletes a project from the trash.
+pub async fn purge_project(handle: &WorkspaceHandle, project_id: Uuid) -> AppResult<Uuid> {
+    let id_str = project_id.to_string();
+    let mut tx = handle.pool.begin().await?;
+    let trashed: bool = sqlx::query_scalar(
+        "SELECT count(*) > 0 FROM projects WHERE id = ? AND deleted_at IS NOT NULL",
+    )
+    .bind(&id_str)
+    .fetch_one(&mut *tx)
+    .await?;
+    if !trashed {
+        return Err(not_in_trash(&id_str));
+    }
+    purge_rows(&mut tx, &id_str).await?;
+    tx.commit().await?;
     Ok(project_id)
 }
 
+/// Permanently deletes every project in the trash.
+pub async fn empty_trash(handle: &WorkspaceHandle) -> AppResult<Vec<Uuid>> {
+    purge_trash(&handle.pool, None).await
+}
+
+/// Purges the projects that have been in the trash for more than
+/// `retention_days`. Zero keeps them until they are purged by hand.
+pub async fn purge_expired(pool: &SqlitePool, retention_days: u32) -> AppResult<Vec<Uuid>> {
+    if retention_days == 0 {
+        return Ok(Vec::new());
+    }
+    purge_trash(pool, Some(retention_days)).await
+}
+
+/// Purges the trash, or only what was deleted more than `older_than_days` ago.
+async fn purge_trash(pool: &SqlitePool, older_than_days: Option<u32>) -> AppResult<Vec<Uuid>> {
+    let mut tx = pool.begin().await?;
+    let ids: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT id FROM projects
+        WHERE deleted_at IS NOT NULL
+          AND (?1 IS NULL OR julianday('now') - julianday(deleted_at) > ?1)
+        "#,
+    )
+    .bind(older_than_days)
+    .fetch_all(&mut *tx)
+    .await?;
+    let mut purged = Vec::with_capacity(ids.len());
+    for id in ids {
+        purge_rows(&mut tx, &id).await?;
+        if let Ok(id) = Uuid::parse_str(&id) {
+            purged.push(id);
+        }
+    }
+    tx.commit().await?;
+    Ok(purged)
+}
+
+/// Deletes a project row with everything that belongs to it.
+async fn purge_rows(conn: &mut SqliteConnection, project_id: &str) -> AppResult<()> {
+    for table in ["launch_configs", "project_tags", "project_notes"] {
+        sqlx::query(&format!("DELETE FROM {table} WHERE project_id = ?"))
+            .bind(project_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+    search::remove_project(conn, project_id).await?;
+    sqlx::query("DELETE FROM projects WHERE id = ?")
+        .bind(project_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+fn not_in_trash(project_id: &str) -> AppError {
+    AppError::Validation(format!("project is not in the trash: {project_id}"))
+}
+
 pub async fn get_project(handle: &WorkspaceHandle, project_id: Uuid) -> AppResult<Project> {
     fetch_project(&handle.pool, &project_id).await
 }
 
+/// Sets the favorite and pinned flags; `None` leaves a flag as it is.
+pub async fn set_project_flags(
+    handle: &WorkspaceHandle,
+    project_id: Uuid,
+    favorite: Option<bool>,
+    pinned: Option<bool>,
+) -> AppResult<Project> {
+    let affected = sqlx::query(
+        r#"
+        UPDATE projects
+        SET favorite = COALESCE(?, favorite), pinned = COALESCE(?, pinned), updated_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(favorite)
+    .bind(pinned)
+    .bind(Utc::now().to_rfc3339())
+    .bind(project_id.to_string())
+    .execute(&handle.pool)
+    .await?
+    .rows_affected();
+    if affected == 0 {
+        return Err(AppError::ProjectNotFound(project_id.to_string()));
+    }
+    get_project(handle, project_id).await
+}
+
+/// Detects the stack of one project, or of every project when `project_id`
+/// is `None`. Projects whose directory is missing keep their last result.
+pub async fn rescan_stacks(
+    handle: &WorkspaceHandle,
+    project_id: Option<Uuid>,
+) -> AppResult<Vec<Project>> {
+    let projects = match project_id {
+        Some(id) => vec![get_project(handle, id).await?],
+        None => list_projects(handle).await?,
+    };
+
+    let mut updated = Vec::with_capacity(projects.len());
+    for mut project in projects {
+        if project.path.is_dir() {
+            let stack = stack::detect(&project.path);
+            let id_str = project.id.to_string();
+            sqlx::query("UPDATE projects SET stack = ? WHERE id = ?")
+                .bind(serde_json::to_string(&stack)?)
+                .bind(&id_str)
+                .execute(&handle.pool)
+                .await?;
+            search::index_project(&mut *handle.pool.acquire().await?, &id_str).await?;
+            project.stack = stack;
+        }
+        updated.push(project);
+    }
+    Ok(updated)
+}
+
+/// Points a project at the directory it was moved to and refreshes what is
+/// detected from its contents.
+pub async fn relocate_project(
+    handle: &WorkspaceHandle,
+    project_id: Uuid,
+    new_path: PathBuf,
+) -> AppResult<Project> {
+    let new_path = sanitize_path_buf(new_path);
+    if !new_path.is_dir() {
+        return Err(AppError::Validation(format!(
+            "project path is not a directory: {}",
+            new_path.display()
+        )));
+    }
+    let owner = project_paths(handle).await?.remove(&path_key(&new_path));
+    if owner.is_some_and(|owner| owner != project_id) {
+        return Err(AppError::Validation(format!(
+            "another project already uses {}",
+            new_path.display()
+        )));
+    }
+
+    let (stack, git_remote) = detect_contents(vec![new_path.clone()]).await?.remove(0);
+    let affected = sqlx::query(
+        "UPDATE projects SET path = ?, stack = ?, git_remote = ?, updated_at = ? WHERE id = ?",
+    )
+    .bind(normalise_path(new_path))
+    .bind(serde_json::to_string(&stack)?)
+    .bind(git_remote)
+    .bind(Utc::now().to_rfc3339())
+    .bind(project_id.to_string())
+    .execute(&handle.pool)
+    .await?
+    .rows_affected();
+    if affected == 0 {
+        return Err(AppError::ProjectNotFound(project_id.to_string()));
+    }
+    search::index_project(&mut *handle.pool.acquire().await?, &project_id.to_string()).await?;
+    get_project(handle, project_id).await
+}
+
+pub async fn git_remote(handle: &WorkspaceHandle, project_id: Uuid) -> AppResult<Option<String>> {
+    sqlx::query_scalar::<_, Option<String>>("SELECT git_remote FROM projects WHERE id = ?")
+        .bind(project_id.to_string())
+        .fetch_optional(&handle.pool)
+        .await?
+        .ok_or_else(|| AppError::ProjectNotFound(project_id.to_string()))
+}
+
+/// A tracked process started by [`launch_project`] with its open session.
+pub struct LaunchedChild {
+    pub child: std::process::Child,
+    pub session_id: Uuid,
+    pub script: Option<TempScript>,
+}
+
+/// Finds a launch configuration by id, or the default one when `config_id`
+/// is not given.
+pub fn resolve_launch_config(
+    project: &Project,
+    config_id: Option<Uuid>,
+) -> AppResult<&LaunchConfig> {
+    let found = match config_id {
+        Some(id) => project.launch_configs.iter().find(|config| config.id == id),
+        None => project
+            .launch_configs
+            .iter()
+            .find(|config| config.is_default)
+            .or_else(|| project.launch_configs.first()),
+    };
+    found.ok_or_else(|| {
+        AppError::Validation(match config_id {
+            Some(id) => format!("launch configuration not found: {id}"),
+            None => format!("project '{}' has no launch configuration", project.name),
+        })
+    })
+}
+
+/// Refuses to launch projects in the trash.
+fn ensure_not_trashed(project: &Project) -> AppResult<()> {
+    match project.deleted_at {
+        Some(_) => Err(AppError::Launch(format!(
+            "project '{}' is in the trash, restore it first",
+            project.name
+        ))),
+        None => Ok(()),
+    }
+}
+
 pub async fn launch_project(
     handle: &WorkspaceHandle,
     project: &Project,
-) -> AppResult<Option<std::process::Child>> {
-    let child = match &project.open_config {
-        OpenConfig::SystemDefault => {
-            open_with_system(&project.path)?;
-            None
-        }
-        OpenConfig::CustomApp { executable, args } => {
-            Some(spawn_with_program(executable, args, &project.path)?)
+    launch_config: &LaunchConfig,
+    instance_id: Uuid,
+    process_name: &str,
+) -> AppResult<Option<LaunchedChild>> {
+    let target = SessionTarget {
+        project_id: project.id,
+        instance_id,
+        config_id: launch_config.id,
+        process_name,
+    };
+    let context = PlaceholderContext {
+        project_path: &project.path,
+        project_name: &project.name,
+        workspace_name: &handle.meta.name,
+    };
+    let spawned = ensure_not_trashed(project)
+        .and_then(|()| health::ensure_available(&project.path))
+        .and_then(|()| spawn_config(&launch_config.config, &project.path, &context));
+
+    let spawned = match spawned {
+        Ok(child) => child,
+        Err(err) => {
+            // Keep the launch error even if the failure cannot be recorded
+            if let Err(record_err) =
+                session::record_spawn_failure(handle, &target, &err.to_string()).await
+            {
+                log::warn!("failed to record spawn failure: {record_err}");
+            }
+            return Err(err);
         }
-        OpenConfig::CustomCommand { command, args } => {
-            Some(spawn_with_command(command, args, &project.path)?)
+    };
+
+    let pid = spawned.as_ref().map(|(child, _)| child.id());
+    let recorded = async {
+        sqlx::query("INSERT INTO launch_history (project_id, launched_at) VALUES (?, ?)")
+            .bind(project.id.to_string())
+            .bind(Utc::now().to_rfc3339())
+            .execute(&handle.pool)
+            .await?;
+        session::open_session(handle, &target, pid).await
+    }
+    .await;
+
+    let session_id = match recorded {
+        Ok(session_id) => session_id,
+        Err(err) => {
+            // An untracked process could never be stopped, so it must not
+            // outlive the failed launch; its script is removed afterwards
+            if let Some((mut child, _script)) = spawned {
+                if let Err(kill_err) = process::terminate(&mut child, Duration::ZERO).await {
+                    log::warn!("failed to kill process {}: {kill_err}", child.id());
+                }
+            }
+            return Err(err);
         }
     };
 
-    let now = Utc::now().to_rfc3339();
-    let project_id = project.id.to_string();
+    Ok(spawned.map(|(child, script)| LaunchedChild {
+        child,
+        session_id,
+        script,
+    }))
+}
 
-    sqlx::query("INSERT INTO launch_history (project_id, launched_at) VALUES (?, ?)")
-        .bind(&project_id)
-        .bind(&now)
-        .execute(&handle.pool)
-        .await?;
+type Spawned = Option<(std::process::Child, Option<TempScript>)>;
 
-    Ok(child)
+/// Expands placeholders and starts `config`. System opens are not tracked and
+/// give `None`.
+fn spawn_config(
+    config: &OpenConfig,
+    project_path: &Path,
+    context: &PlaceholderContext,
+) -> AppResult<Spawned> {
+    let cwd = |cwd: &Option<PathBuf>| {
+        cwd.as_deref()
+            .map(|cwd| context.expand_path(cwd))
+            .transpose()
+    };
+    match config {
+        OpenConfig::SystemDefault => open_with_system(project_path).map(|_| None),
+        OpenConfig::CustomApp {
+            executable,
+            args,
+            env,
+            cwd: dir,
+        } => {
+            let mut expanded_args = context.expand_args(args)?;
+            if !placeholders::references_project_path(args) {
+                expanded_args.push(project_path.to_string_lossy().to_string());
+            }
+            spawn_with_program(
+                &context.expand_path(executable)?,
+                &expanded_args,
+                project_path,
+                &context.expand_env(env)?,
+                cwd(dir)?.as_deref(),
+            )
+            .map(|child| Some((child, None)))
+        }
+        OpenConfig::CustomCommand {
+            command,
+            args,
+            env,
+            cwd: dir,
+        } => spawn_with_command(
+            &context.expand(command)?,
+            &context.expand_args(args)?,
+            project_path,
+            &context.expand_env(env)?,
+            cwd(dir)?.as_deref(),
+        )
+        .map(|child| Some((child, None))),
+        OpenConfig::ShellCommand {
+            command,
+            env,
+            cwd: dir,
+        } => spawn_with_shell(
+            &context.expand_shell(command)?,
+            project_path,
+            &context.expand_env(env)?,
+            cwd(dir)?.as_deref(),
+        )
+        .map(|child| Some((child, None))),
+        OpenConfig::InlineScript {
+            interpreter,
+            script,
+            env,
+            cwd: dir,
+        } => spawn_inline_script(
+            *interpreter,
+            script,
+            project_path,
+            &context.expand_env(env)?,
+            cwd(dir)?.as_deref(),
+        )
+        .map(|(child, script)| Some((child, Some(script)))),
+        OpenConfig::Preset { preset_id, .. } => Err(AppError::Launch(format!(
+            "launch preset {preset_id} was not resolved"
+        ))),
+    }
 }
 
-pub fn stop_project(child: &mut std::process::Child) -> AppResult<()> {
-    child
-        .kill()
-        .map_err(|err| AppError::Launch(format!("failed to kill process: {err}")))
+pub async fn stop_project(
+    child: &mut std::process::Child,
+    grace_period: Duration,
+) -> AppResult<StopOutcome> {
+    process::terminate(child, grace_period).await
 }
 
 pub async fn get_activity_stats(handle: &WorkspaceHandle) -> AppResult<ActivityStats> {
@@ -209,10 +840,11 @@ pub async fn get_activity_stats(handle: &WorkspaceHandle) -> AppResult<ActivityS
         .await
         .unwrap_or(0);
 
-    let total_projects: i64 = sqlx::query_scalar("SELECT count(*) FROM projects")
-        .fetch_one(&handle.pool)
-        .await
-        .unwrap_or(0);
+    let total_projects: i64 =
+        sqlx::query_scalar("SELECT count(*) FROM projects WHERE deleted_at IS NULL")
+            .fetch_one(&handle.pool)
+            .await
+            .unwrap_or(0);
 
     // 4. Project counts
     let project_rows = sqlx::query(
@@ -220,6 +852,7 @@ pub async fn get_activity_stats(handle: &WorkspaceHandle) -> AppResult<ActivityS
         SELECT p.name, count(lh.id) as count
         FROM projects p
         LEFT JOIN launch_history lh ON p.id = lh.project_id
+        WHERE p.deleted_at IS NULL
         GROUP BY p.id
         ORDER BY count DESC
         LIMIT 10
@@ -258,27 +891,146 @@ async fn fetch_project(pool: &SqlitePool, project_id: &Uuid) -> AppResult<Projec
         .bind(&id_str)
         .fetch_one(pool)
         .await?;
-    row_to_project(row)
+    let configs = load_launch_configs(pool, Some(&id_str))
+        .await?
+        .remove(&id_str)
+        .unwrap_or_default();
+    let tags = tags::load_tags(pool, Some(&id_str))
+        .await?
+        .remove(&id_str)
+        .unwrap_or_default();
+    let frecency = frecency::project_scores(pool, Some(&id_str))
+        .await?
+        .remove(&id_str)
+        .unwrap_or(0);
+    row_to_project(row, configs, tags, frecency)
 }
 
-fn row_to_project(row: ProjectRow) -> AppResult<Project> {
+/// Loads launch configurations in their saved order, grouped by project id.
+async fn load_launch_configs<'c>(
+    executor: impl SqliteExecutor<'c>,
+    project_id: Option<&str>,
+) -> AppResult<HashMap<String, Vec<LaunchConfig>>> {
+    let rows = sqlx::query_as::<_, LaunchConfigRow>(&format!(
+        "{LAUNCH_CONFIG_SELECT} WHERE (?1 IS NULL OR project_id = ?1) ORDER BY project_id, position"
+    ))
+    .bind(project_id)
+    .fetch_all(executor)
+    .await?;
+
+    let mut grouped: HashMap<String, Vec<LaunchConfig>> = HashMap::new();
+    for row in rows {
+        let id = Uuid::parse_str(&row.id).map_err(|err| {
+            AppError::Validation(format!(
+                "invalid launch configuration id '{}': {err}",
+                row.id
+            ))
+        })?;
+        let config: OpenConfig = serde_json::from_str(&row.config)?;
+        grouped
+            .entry(row.project_id)
+            .or_default()
+            .push(LaunchConfig {
+                id,
+                name: row.name,
+                is_default: row.is_default,
+                config,
+            });
+    }
+    Ok(grouped)
+}
+
+/// The stored state of a project about to be updated, without tags.
+/// Projects in the trash are not found, as they cannot be edited.
+async fn load_stored_project(conn: &mut SqliteConnection, project_id: Uuid) -> AppResult<Project> {
+    let id_str = project_id.to_string();
+    let row = sqlx::query_as::<_, ProjectRow>(&format!(
+        "{PROJECT_SELECT} WHERE id = ? AND deleted_at IS NULL"
+    ))
+    .bind(&id_str)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| AppError::ProjectNotFound(id_str.clone()))?;
+    let configs = load_launch_configs(&mut *conn, Some(&id_str))
+        .await?
+        .remove(&id_str)
+        .unwrap_or_default();
+    row_to_project(row, configs, Vec::new(), 0)
+}
+
+/// The stored launch configurations as inputs, keeping their ids. A payload
+/// with only an `open_config`, as sent by older clients, replaces just the
+/// default configuration.
+fn stored_launch_configs(
+    stored: &Project,
+    open_config: Option<OpenConfig>,
+) -> Vec<LaunchConfigInput> {
+    let default_id = stored
+        .launch_configs
+        .iter()
+        .find(|config| config.is_default)
+        .or_else(|| stored.launch_configs.first())
+        .map(|config| config.id);
+    stored
+        .launch_configs
+        .iter()
+        .map(|config| LaunchConfigInput {
+            id: Some(config.id),
+            name: config.name.clone(),
+            is_default: Some(config.id) == default_id,
+            config: match &open_config {
+                Some(open_config) if Some(config.id) == default_id => open_config.clone(),
+                _ => config.config.clone(),
+            },
+        })
+        .collect()
+}
+
+fn row_to_project(
+    row: ProjectRow,
+    launch_configs: Vec<LaunchConfig>,
+    tags: Vec<Tag>,
+    frecency: i64,
+) -> AppResult<Project> {
     let id = Uuid::parse_str(&row.id)
         .map_err(|err| AppError::Validation(format!("invalid project id '{}': {err}", row.id)))?;
     let created_at = parse_timestamp(&row.created_at)?;
     let updated_at = parse_timestamp(&row.updated_at)?;
-    let open_config: OpenConfig = serde_json::from_str(&row.open_config)?;
+    let open_config = launch_configs
+        .iter()
+        .find(|config| config.is_default)
+        .map(|config| config.config.clone())
+        .unwrap_or_default();
+    let restart_policy: RestartPolicy = match row.restart_policy.as_deref() {
+        Some(json) => serde_json::from_str(json)?,
+        None => RestartPolicy::default(),
+    };
+    let stack = match row.stack.as_deref() {
+        Some(json) => serde_json::from_str(json)?,
+        None => ProjectStack::default(),
+    };
     Ok(Project {
         id,
         name: row.name,
         path: PathBuf::from(row.path),
         description: row.description,
         open_config,
+        launch_configs,
+        stop_grace_period_secs: row.stop_grace_period_secs.map(|secs| secs.max(0) as u64),
+        restart_policy,
+        stack,
+        tags,
+        favorite: row.favorite,
+        pinned: row.pinned,
+        frecency,
+        git: None,
+        deleted_at: row.deleted_at.as_deref().map(parse_timestamp).transpose()?,
         created_at,
         updated_at,
     })
 }
 
-fn parse_timestamp(value: &str) -> AppResult<DateTime<Utc>> {
+pub(crate) fn parse_timestamp(value: &str) -> AppResult<DateTime<Utc>> {
     Ok(DateTime::parse_from_rfc3339(value)
         .map_err(|err| AppError::Validation(format!("invalid timestamp '{}': {err}", value)))?
         .with_timezone(&Utc))
@@ -288,42 +1040,249 @@ fn normalise_path(path: PathBuf) -> String {
     path.to_string_lossy().to_string()
 }
 
+/// Validates launch configurations in the order given. When none is given,
+/// `fallback` becomes the only, default, configuration; when none is marked
+/// as default, the first one is.
+fn prepare_launch_configs(
+    inputs: Vec<LaunchConfigInput>,
+    fallback: OpenConfig,
+) -> AppResult<Vec<LaunchConfig>> {
+    let inputs = if inputs.is_empty() {
+        vec![LaunchConfigInput {
+            id: None,
+            name: DEFAULT_LAUNCH_CONFIG_NAME.to_string(),
+            is_default: true,
+            config: fallback,
+        }]
+    } else {
+        inputs
+    };
+
+    let default_count = inputs.iter().filter(|input| input.is_default).count();
+    if default_count > 1 {
+        return Err(AppError::Validation(
+            "only one launch configuration can be the default".to_string(),
+        ));
+    }
+
+    let mut configs: Vec<LaunchConfig> = Vec::with_capacity(inputs.len());
+    for (index, mut input) in inputs.into_iter().enumerate() {
+        let name = input.name.trim().to_string();
+        if name.is_empty() {
+            return Err(AppError::Validation(
+                "launch configuration name cannot be empty".to_string(),
+            ));
+        }
+        if configs
+            .iter()
+            .any(|config| config.name.eq_ignore_ascii_case(&name))
+        {
+            return Err(AppError::Validation(format!(
+                "launch configuration name '{name}' is used twice"
+            )));
+        }
+
+        if let OpenConfig::CustomApp { executable, .. } = &mut input.config {
+            *executable = sanitize_path_buf(executable.clone());
+        }
+        validate_open_config(&input.config)?;
+
+        let id = input.id.unwrap_or_else(Uuid::new_v4);
+        if configs.iter().any(|config| config.id == id) {
+            return Err(AppError::Validation(format!(
+                "launch configuration id '{id}' is used twice"
+            )));
+        }
+
+        configs.push(LaunchConfig {
+            id,
+            name,
+            is_default: input.is_default || (default_count == 0 && index == 0),
+            config: input.config,
+        });
+    }
+    Ok(configs)
+}
+
 pub(crate) fn validate_open_config(config: &OpenConfig) -> AppResult<()> {
+    placeholders::validate_config(config)?;
     match config {
         OpenConfig::SystemDefault => Ok(()),
-        OpenConfig::CustomApp { executable, .. } => {
+        OpenConfig::CustomApp {
+            executable,
+            env,
+            cwd,
+            ..
+        } => {
             if executable.as_os_str().is_empty() {
                 return Err(AppError::Validation(
                     "startup program cannot be empty".to_string(),
                 ));
             }
-            if !executable.exists() {
+            // Programs given through placeholders can only be checked at launch
+            let templated = placeholders::has_placeholders(&executable.to_string_lossy());
+            if !templated && !executable.exists() {
                 return Err(AppError::Validation(format!(
                     "startup program not found: {}",
                     executable.display()
                 )));
             }
-            Ok(())
+            validate_launch_env(env)?;
+            validate_cwd(cwd.as_deref())
         }
-        OpenConfig::CustomCommand { command, .. } => {
+        OpenConfig::CustomCommand {
+            command, env, cwd, ..
+        } => {
             if command.trim().is_empty() {
                 return Err(AppError::Validation(
                     "custom command cannot be empty".to_string(),
                 ));
             }
-            Ok(())
+            validate_launch_env(env)?;
+            validate_cwd(cwd.as_deref())
+        }
+        OpenConfig::ShellCommand { command, env, cwd } => {
+            if command.trim().is_empty() {
+                return Err(AppError::Validation(
+                    "shell command cannot be empty".to_string(),
+                ));
+            }
+            validate_launch_env(env)?;
+            validate_cwd(cwd.as_deref())
+        }
+        OpenConfig::InlineScript {
+            script, env, cwd, ..
+        } => {
+            if script.trim().is_empty() {
+                return Err(AppError::Validation(
+                    "inline script cannot be empty".to_string(),
+                ));
+            }
+            validate_launch_env(env)?;
+            validate_cwd(cwd.as_deref())
+        }
+        OpenConfig::Preset { overrides, .. } => {
+            validate_env_changes(&overrides.env)?;
+            validate_cwd(overrides.cwd.as_deref())
+        }
+    }
+}
+
+fn validate_launch_env(env: &LaunchEnv) -> AppResult<()> {
+    validate_env_changes(&env.vars)
+}
+
+fn validate_env_changes(changes: &[EnvChange]) -> AppResult<()> {
+    for change in changes {
+        match change {
+            EnvChange::Set { name, value } => {
+                validate_env_name(name)?;
+                if value.contains('\0') {
+                    return Err(AppError::Validation(format!(
+                        "environment variable '{name}' cannot contain a NUL character"
+                    )));
+                }
+            }
+            EnvChange::Unset { name } => validate_env_name(name)?,
+            EnvChange::AppendPath { entry } => {
+                if entry.trim().is_empty() {
+                    return Err(AppError::Validation(
+                        "PATH entry cannot be empty".to_string(),
+                    ));
+                }
+                if std::env::join_paths([entry]).is_err() {
+                    return Err(AppError::Validation(format!(
+                        "PATH entry '{entry}' contains a path separator"
+                    )));
+                }
+            }
         }
     }
+    Ok(())
 }
 
+fn validate_env_name(name: &str) -> AppResult<()> {
+    if name.trim().is_empty() {
+        return Err(AppError::Validation(
+            "environment variable name cannot be empty".to_string(),
+        ));
+    }
+    if name.contains(['=', '\0']) {
+        return Err(AppError::Validation(format!(
+            "invalid environment variable name '{name}'"
+        )));
+    }
+    Ok(())
+}
+
+fn validate_cwd(cwd: Option<&Path>) -> AppResult<()> {
+    let Some(cwd) = cwd else {
+        return Ok(());
+    };
+    if cwd.is_absolute() {
+        return Err(AppError::Validation(format!(
+            "working directory must be relative to the project path: {}",
+            cwd.display()
+        )));
+    }
+    if cwd
+        .components()
+        .any(|component| matches!(component, Component::ParentDir))
+    {
+        return Err(AppError::Validation(format!(
+            "working directory cannot leave the project path: {}",
+            cwd.display()
+        )));
+    }
+    Ok(())
+}
+
+fn validate_stop_grace_period(secs: Option<u64>) -> AppResult<()> {
+    match secs {
+        Some(secs) if secs > MAX_STOP_GRACE_PERIOD_SECS => Err(AppError::Validation(format!(
+            "stop grace period cannot exceed {MAX_STOP_GRACE_PERIOD_SECS} seconds"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn validate_restart_policy(policy: &RestartPolicy) -> AppResult<()> {
+    if policy.mode == RestartMode::Never {
+        return Ok(());
+    }
+    if policy.max_retries == 0 {
+        return Err(AppError::Validation(
+            "restart policy needs at least one retry".to_string(),
+        ));
+    }
+    if policy.window_secs == 0 {
+        return Err(AppError::Validation(
+            "restart window cannot be zero".to_string(),
+        ));
+    }
+    if policy.initial_backoff_ms > policy.max_backoff_ms {
+        return Err(AppError::Validation(
+            "initial restart backoff cannot exceed the maximum backoff".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Starts a program. `args` already end with the project path unless the
+/// config placed it itself.
 fn spawn_with_program(
     executable: &Path,
     args: &[String],
     project_path: &Path,
+    env: &LaunchEnv,
+    cwd: Option<&Path>,
 ) -> AppResult<std::process::Child> {
     let mut command = Command::new(executable);
     command.args(args.iter());
-    command.arg(project_path);
+    if let Some(cwd) = cwd {
+        command.current_dir(resolve_cwd(project_path, cwd)?);
+    }
+    apply_launch_env(&mut command, env)?;
     let label = format!("program {}", executable.display());
     spawn_child(command, &label)
 }
@@ -332,13 +1291,145 @@ fn spawn_with_command(
     command_name: &str,
     args: &[String],
     project_path: &Path,
+    env: &LaunchEnv,
+    cwd: Option<&Path>,
 ) -> AppResult<std::process::Child> {
     let mut command = Command::new(command_name);
     command.args(args.iter());
-    command.current_dir(project_path); // Set CWD for custom commands
+    command.current_dir(command_dir(project_path, cwd)?); // Set CWD for custom commands
+    apply_launch_env(&mut command, env)?;
     spawn_child(command, command_name)
 }
 
+fn spawn_with_shell(
+    command_line: &str,
+    project_path: &Path,
+    env: &LaunchEnv,
+    cwd: Option<&Path>,
+) -> AppResult<std::process::Child> {
+    let mut command = shell_command(command_line);
+    command.current_dir(command_dir(project_path, cwd)?);
+    apply_launch_env(&mut command, env)?;
+    spawn_child(command, "shell")
+}
+
+/// Runs the user's login shell so that profile changes like `nvm` or
+/// `pyenv` shims are on the `PATH`, falling back to `/bin/sh`.
+#[cfg(unix)]
+fn shell_command(command_line: &str) -> Command {
+    let shell = std::env::var_os("SHELL")
+        .filter(|shell| !shell.is_empty())
+        .unwrap_or_else(|| "/bin/sh".into());
+    let mut command = Command::new(shell);
+    command.arg("-lc").arg(command_line);
+    command
+}
+
+#[cfg(not(unix))]
+fn shell_command(command_line: &str) -> Command {
+    let shell = std::env::var_os("COMSPEC").unwrap_or_else(|| "cmd.exe".into());
+    let mut command = Command::new(shell);
+    command.arg("/C").arg(command_line);
+    command
+}
+
+fn spawn_inline_script(
+    interpreter: ScriptInterpreter,
+    script: &str,
+    project_path: &Path,
+    env: &LaunchEnv,
+    cwd: Option<&Path>,
+) -> AppResult<(std::process::Child, TempScript)> {
+    let file = TempScript::write(script, interpreter.extension())?;
+    let mut command = Command::new(interpreter.program());
+    command.arg(file.path());
+    command.current_dir(command_dir(project_path, cwd)?);
+    apply_launch_env(&mut command, env)?;
+    let child = spawn_child(command, interpreter.program())?;
+    Ok((child, file))
+}
+
+/// Working directory for commands, which run in the project directory unless
+/// the config says otherwise.
+fn command_dir(project_path: &Path, cwd: Option<&Path>) -> AppResult<PathBuf> {
+    match cwd {
+        Some(cwd) => resolve_cwd(project_path, cwd),
+        None => Ok(project_path.to_path_buf()),
+    }
+}
+
+/// Joins `cwd` onto the project path, refusing directories outside the
+/// project, e.g. reached through a symlink or an expanded placeholder.
+fn resolve_cwd(project_path: &Path, cwd: &Path) -> AppResult<PathBuf> {
+    let dir = project_path.join(cwd);
+    if !dir.is_dir() {
+        return Err(AppError::Launch(format!(
+            "working directory not found: {}",
+            dir.display()
+        )));
+    }
+    let inside = match (dir.canonicalize(), project_path.canonicalize()) {
+        (Ok(dir), Ok(project)) => dir.starts_with(project),
+        _ => false,
+    };
+    if !inside {
+        return Err(AppError::Launch(format!(
+            "working directory is outside the project path: {}",
+            dir.display()
+        )));
+    }
+    Ok(dir)
+}
+
+/// Applies `env` on top of the inherited environment. `PATH` is tracked
+/// locally so appends see earlier sets and unsets from the same list.
+fn apply_launch_env(command: &mut Command, env: &LaunchEnv) -> AppResult<()> {
+    let mut path = if env.clear {
+        command.env_clear();
+        None
+    } else {
+        std::env::var_os("PATH")
+    };
+
+    for change in &env.vars {
+        match change {
+            EnvChange::Set { name, value } => {
+                if is_path_var(name) {
+                    path = Some(value.into());
+                }
+                command.env(name, value);
+            }
+            EnvChange::Unset { name } => {
+                if is_path_var(name) {
+                    path = None;
+                }
+                command.env_remove(name);
+            }
+            EnvChange::AppendPath { entry } => {
+                let mut entries: Vec<PathBuf> = path
+                    .as_deref()
+                    .map(|current| std::env::split_paths(current).collect())
+                    .unwrap_or_default();
+                entries.push(PathBuf::from(entry));
+                let joined = std::env::join_paths(entries).map_err(|err| {
+                    AppError::Launch(format!("failed to extend PATH with '{entry}': {err}"))
+                })?;
+                command.env("PATH", &joined);
+                path = Some(joined);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_path_var(name: &str) -> bool {
+    if cfg!(windows) {
+        name.eq_ignore_ascii_case("PATH")
+    } else {
+        name == "PATH"
+    }
+}
+
 fn open_with_system(path: &Path) -> AppResult<()> {
     #[cfg(target_os = "windows")]
     {
@@ -377,7 +1468,12 @@ fn spawn_detached(mut command: Command, label: &str) -> AppResult<()> {
 }
 
 fn spawn_child(mut command: Command, label: &str) -> AppResult<std::process::Child> {
+    // Output is drained by `process::capture_output` once the child is tracked
+    process::isolate_process_group(&mut command);
     command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
         .spawn()
         .map_err(|err| AppError::Launch(format!("{label}: {err}")))
 }
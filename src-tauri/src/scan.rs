@@ -0,0 +1,223 @@
+use std::path::{Path, PathBuf};
+
+use glob::Pattern;
+use walkdir::{DirEntry, WalkDir};
+
+use crate::{
+    error::{AppError, AppResult},
+    models::ScanCandidate,
+    stack,
+};
+
+pub const DEFAULT_SCAN_DEPTH: usize = 4;
+
+/// Files and directories whose presence makes a directory a project root.
+const PROJECT_MARKERS: [&str; 18] = [
+    ".git",
+    "package.json",
+    "Cargo.toml",
+    "pom.xml",
+    "build.gradle",
+    "build.gradle.kts",
+    "go.mod",
+    "pyproject.toml",
+    "setup.py",
+    "requirements.txt",
+    "CMakeLists.txt",
+    "meson.build",
+    "composer.json",
+    "Gemfile",
+    "mix.exs",
+    "pubspec.yaml",
+    "Package.swift",
+    "deno.json",
+];
+
+/// Directories that never contain project roots of their own.
+const SKIPPED_DIRS: [&str; 8] = [
+    "node_modules",
+    "target",
+    "build",
+    "dist",
+    "vendor",
+    "venv",
+    "__pycache__",
+    "obj",
+];
+
+pub fn compile_globs(globs: &[String]) -> AppResult<Vec<Pattern>> {
+    globs
+        .iter()
+        .map(|glob| {
+            Pattern::new(glob.trim()).map_err(|err| {
+                AppError::Validation(format!("invalid ignore pattern '{glob}': {err}"))
+            })
+        })
+        .collect()
+}
+
+/// Walks `root` up to `max_depth` levels deep and returns the project roots
+/// found. The walk does not descend into a project once its root is found,
+/// nor into hidden, dependency or build output directories.
+pub fn find_projects(root: &Path, max_depth: usize, ignore: &[Pattern]) -> Vec<ScanCandidate> {
+    let mut candidates = Vec::new();
+    let mut walker = WalkDir::new(root)
+        .max_depth(max_depth)
+        .follow_links(false)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| entry.file_type().is_dir() && !is_skipped(root, entry, ignore));
+
+    while let Some(entry) = walker.next() {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                log::warn!("skipping unreadable directory while scanning: {err}");
+                continue;
+            }
+        };
+
+        let Some(candidate) = candidate_at(entry.path()) else {
+            continue;
+        };
+        candidates.push(candidate);
+        // Nested manifests belong to the project just found
+        if entry.depth() > 0 {
+            walker.skip_current_dir();
+        }
+    }
+    candidates
+}
+
+/// Directories to watch for new projects below `root`: those up to
+/// `max_depth` levels deep that are neither ignored nor inside a project, so
+/// that builds and dependency installs never reach the watcher.
+pub fn watch_dirs(root: &Path, max_depth: usize, ignore: &[Pattern]) -> Vec<PathBuf> {
+    WalkDir::new(root)
+        .max_depth(max_depth)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.file_type().is_dir()
+                && !is_skipped(root, entry, ignore)
+                && (entry.depth() == 0 || project_markers(entry.path()).is_empty())
+        })
+        .filter_map(Result::ok)
+        .map(DirEntry::into_path)
+        .collect()
+}
+
+/// Describes `dir` as a candidate if it holds a project marker.
+pub fn candidate_at(dir: &Path) -> Option<ScanCandidate> {
+    let markers = project_markers(dir);
+    if markers.is_empty() {
+        return None;
+    }
+    Some(ScanCandidate {
+        path: dir.to_path_buf(),
+        name: dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        markers,
+        stack: stack::detect(dir),
+        existing_project_id: None,
+    })
+}
+
+fn project_markers(dir: &Path) -> Vec<String> {
+    PROJECT_MARKERS
+        .iter()
+        .filter(|marker| dir.join(marker).exists())
+        .map(|marker| marker.to_string())
+        .collect()
+}
+
+fn is_skipped(root: &Path, entry: &DirEntry, ignore: &[Pattern]) -> bool {
+    entry.depth() > 0 && is_ignored(root, entry.path(), ignore)
+}
+
+/// Whether the directory at `path`, somewhere below `root`, is left out of
+/// scans.
+pub fn is_ignored(root: &Path, path: &Path, ignore: &[Pattern]) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    if name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_ref()) {
+        return true;
+    }
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    ignore
+        .iter()
+        .any(|pattern| pattern.matches(&name) || pattern.matches_path(relative))
+}
+
+/// Finds the project root that `path` belongs to: the directory closest to
+/// `root`, at most `max_depth` levels below it, that holds a project marker.
+pub fn enclosing_project(
+    root: &Path,
+    path: &Path,
+    max_depth: usize,
+    ignore: &[Pattern],
+) -> Option<PathBuf> {
+    let relative = path.strip_prefix(root).ok()?;
+    let mut dir = root.to_path_buf();
+    for component in relative.components().take(max_depth) {
+        dir.push(component);
+        if !dir.is_dir() || is_ignored(root, &dir, ignore) {
+            return None;
+        }
+        if PROJECT_MARKERS
+            .iter()
+            .any(|marker| dir.join(marker).exists())
+        {
+            return Some(dir);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn enclosing_project_finds_the_root_closest_to_the_scan_root() {
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("group/app");
+        fs::create_dir_all(project.join("packages/ui/src")).unwrap();
+        fs::write(project.join("package.json"), "{}").unwrap();
+        fs::write(project.join("packages/ui/package.json"), "{}").unwrap();
+
+        let file = project.join("packages/ui/src/index.ts");
+        assert_eq!(
+            enclosing_project(root.path(), &file, 4, &[]),
+            Some(project.clone())
+        );
+        assert_eq!(enclosing_project(root.path(), &file, 1, &[]), None);
+        assert_eq!(
+            enclosing_project(root.path(), &root.path().join("group/other"), 4, &[]),
+            None
+        );
+        assert_eq!(
+            enclosing_project(Path::new("/elsewhere"), &file, 4, &[]),
+            None
+        );
+    }
+
+    #[test]
+    fn enclosing_project_skips_ignored_directories() {
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("archive/app");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join("Cargo.toml"), "").unwrap();
+
+        let ignore = compile_globs(&["archive".to_string()]).unwrap();
+        let file = project.join("Cargo.toml");
+        assert_eq!(enclosing_project(root.path(), &file, 4, &ignore), None);
+        assert_eq!(enclosing_project(root.path(), &file, 4, &[]), Some(project));
+    }
+}
@@ -0,0 +1,429 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+};
+
+use sqlx::{SqliteConnection, SqlitePool};
+use uuid::Uuid;
+
+use crate::{
+    error::AppResult,
+    frecency,
+    models::{Project, ProjectSearchResult, ProjectStack},
+    project,
+    state::WorkspaceHandle,
+};
+
+pub const DEFAULT_SEARCH_LIMIT: usize = 20;
+const MAX_SEARCH_LIMIT: usize = 100;
+
+const INDEX_SCHEMA: &str = r#"
+CREATE VIRTUAL TABLE IF NOT EXISTS project_search USING fts5(
+  project_id UNINDEXED,
+  name,
+  description,
+  path,
+  tags,
+  stack,
+  notes,
+  tokenize = 'unicode61 remove_diacritics 2',
+  prefix = '2 3'
+)
+"#;
+/// bm25 weights of the `project_search` columns, in table order.
+const BM25_WEIGHTS: &str = "0.0, 10.0, 2.0, 1.0, 5.0, 3.0, 1.0";
+/// Indexed fields as matched by the fuzzy scorer, with their weights.
+const FIELDS: [(&str, f64); 6] = [
+    ("name", 1.0),
+    ("description", 0.5),
+    ("path", 0.6),
+    ("tags", 0.8),
+    ("stack", 0.7),
+    ("notes", 0.4),
+];
+/// Score of a term that only matches as a subsequence of the name.
+const SUBSEQUENCE_SCORE: f64 = 0.4;
+/// Share of the relevance that comes from the full-text rank.
+const TEXT_RANK_WEIGHT: f64 = 0.25;
+
+const DOCUMENT_COLUMNS: &str = "project_id, name, description, path, tags, stack, notes";
+
+/// An indexed project: id, name, description, path, tags, stack and notes.
+type SearchDocument = (String, String, String, String, String, String, String);
+/// A [`SearchDocument`] found by the full-text index, with its bm25 rank.
+type RankedDocument = (String, String, String, String, String, String, String, f64);
+
+/// Writes the index entry of a project from its current row, tags and notes.
+/// Projects in the trash are left out of the index.
+pub async fn index_project(conn: &mut SqliteConnection, project_id: &str) -> AppResult<()> {
+    remove_project(conn, project_id).await?;
+    let row: Option<(String, Option<String>, String, Option<String>)> = sqlx::query_as(
+        "SELECT name, description, path, stack FROM projects WHERE id = ? AND deleted_at IS NULL",
+    )
+    .bind(project_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some((name, description, path, stack)) = row else {
+        return Ok(());
+    };
+    let tags: Vec<String> = sqlx::query_scalar(
+        "SELECT t.name FROM project_tags pt JOIN tags t ON t.id = pt.tag_id WHERE pt.project_id = ?",
+    )
+    .bind(project_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let notes: Vec<(String, String)> =
+        sqlx::query_as("SELECT title, body FROM project_notes WHERE project_id = ?")
+            .bind(project_id)
+            .fetch_all(&mut *conn)
+            .await?;
+    let notes_text = notes
+        .iter()
+        .map(|(title, body)| format!("{title}\n{body}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let stack: ProjectStack = match stack.as_deref() {
+        Some(json) => serde_json::from_str(json)?,
+        None => ProjectStack::default(),
+    };
+    let stack_terms = stack
+        .languages
+        .iter()
+        .chain(&stack.frameworks)
+        .chain(&stack.build_systems)
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" ");
+    // Segments rather than the raw path, so that each directory is a term
+    let path_segments = path
+        .split(['/', '\\'])
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    sqlx::query(
+        r#"
+        INSERT INTO project_search (project_id, name, description, path, tags, stack, notes)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(project_id)
+    .bind(&name)
+    .bind(description.unwrap_or_default())
+    .bind(&path_segments)
+    .bind(tags.join(" "))
+    .bind(&stack_terms)
+    .bind(&notes_text)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+pub async fn index_projects(conn: &mut SqliteConnection, project_ids: &[String]) -> AppResult<()> {
+    for project_id in project_ids {
+        index_project(conn, project_id).await?;
+    }
+    Ok(())
+}
+
+pub async fn remove_project(conn: &mut SqliteConnection, project_id: &str) -> AppResult<()> {
+    sqlx::query("DELETE FROM project_search WHERE project_id = ?")
+        .bind(project_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Creates the index and rebuilds it when it does not cover exactly the
+/// projects table, e.g. for databases created before the index existed.
+/// An index that predates a column is recreated.
+pub async fn ensure_index(pool: &SqlitePool) -> AppResult<()> {
+    let has_notes: bool = sqlx::query_scalar(
+        "SELECT count(*) > 0 FROM pragma_table_info('project_search') WHERE name = 'notes'",
+    )
+    .fetch_one(pool)
+    .await?;
+    if !has_notes {
+        sqlx::query("DROP TABLE IF EXISTS project_search")
+            .execute(pool)
+            .await?;
+    }
+    sqlx::query(INDEX_SCHEMA).execute(pool).await?;
+
+    let in_sync: bool = sqlx::query_scalar(
+        r#"
+        SELECT (SELECT count(*) FROM projects WHERE deleted_at IS NULL)
+            = (SELECT count(*) FROM project_search)
+          AND NOT EXISTS (
+            SELECT 1 FROM projects p
+            WHERE p.deleted_at IS NULL
+              AND NOT EXISTS (SELECT 1 FROM project_search s WHERE s.project_id = p.id)
+          )
+        "#,
+    )
+    .fetch_one(pool)
+    .await?;
+    if in_sync {
+        return Ok(());
+    }
+    rebuild_index(pool).await
+}
+
+/// Reindexes every project from scratch.
+pub async fn rebuild_index(pool: &SqlitePool) -> AppResult<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM project_search")
+        .execute(&mut *tx)
+        .await?;
+    let project_ids: Vec<String> =
+        sqlx::query_scalar("SELECT id FROM projects WHERE deleted_at IS NULL")
+            .fetch_all(&mut *tx)
+            .await?;
+    index_projects(&mut tx, &project_ids).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Finds projects matching every term of `query`, tolerating typos, and
+/// ranks them by relevance weighted by launch frecency. Candidates come from
+/// the full-text index; the whole workspace is only scanned for typos when
+/// the index finds fewer than `limit` projects.
+pub async fn search_projects(
+    handle: &WorkspaceHandle,
+    query: &str,
+    limit: Option<usize>,
+) -> AppResult<Vec<ProjectSearchResult>> {
+    let terms = tokenize(query);
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+    let limit = limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+
+    // Terms are alphanumeric, so quoting them is enough to escape them
+    let match_expression = terms
+        .iter()
+        .map(|term| format!("\"{term}\"*"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let hits: Vec<RankedDocument> = sqlx::query_as(&format!(
+        "SELECT {DOCUMENT_COLUMNS}, bm25(project_search, {BM25_WEIGHTS}) FROM project_search \
+         WHERE project_search MATCH ?"
+    ))
+    .bind(&match_expression)
+    .fetch_all(&handle.pool)
+    .await?;
+    // bm25 is lower for better matches
+    let best_rank = hits.iter().map(|hit| -hit.7).fold(0.0, f64::max);
+    let mut documents: Vec<(SearchDocument, f64)> = hits
+        .into_iter()
+        .map(|(id, name, description, path, tags, stack, notes, rank)| {
+            let rank = if best_rank > 0.0 {
+                -rank / best_rank
+            } else {
+                0.0
+            };
+            ((id, name, description, path, tags, stack, notes), rank)
+        })
+        .collect();
+
+    if documents.len() < limit {
+        let found: HashSet<String> = documents
+            .iter()
+            .map(|(document, _)| document.0.clone())
+            .collect();
+        let rest: Vec<SearchDocument> =
+            sqlx::query_as(&format!("SELECT {DOCUMENT_COLUMNS} FROM project_search"))
+                .fetch_all(&handle.pool)
+                .await?;
+        documents.extend(
+            rest.into_iter()
+                .filter(|document| !found.contains(&document.0))
+                .map(|document| (document, 0.0)),
+        );
+    }
+    let frecency = frecency::project_scores(&handle.pool, None).await?;
+
+    let mut scored: Vec<(String, f64, Vec<String>)> = documents
+        .into_iter()
+        .filter_map(|(document, text_rank)| {
+            let (id, name, description, path, tags, stack, notes) = document;
+            let fields = [&name, &description, &path, &tags, &stack, &notes];
+            // Index hits stay even if the tokenizers disagree, e.g. on accents
+            let (relevance, matched_fields) = match fuzzy_score(&terms, fields) {
+                Some(scored) => scored,
+                None if text_rank > 0.0 => (0.0, Vec::new()),
+                None => return None,
+            };
+            let relevance = relevance + TEXT_RANK_WEIGHT * text_rank;
+            let score = frecency::weigh(relevance, frecency.get(&id).copied().unwrap_or(0));
+            Some((id, score, matched_fields))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+    scored.truncate(limit);
+
+    let ids: Vec<Uuid> = scored
+        .iter()
+        .filter_map(|(id, _, _)| Uuid::parse_str(id).ok())
+        .collect();
+    let mut projects: HashMap<Uuid, Project> = project::get_projects(handle, &ids)
+        .await?
+        .into_iter()
+        .map(|project| (project.id, project))
+        .collect();
+    Ok(scored
+        .into_iter()
+        .filter_map(|(id, score, matched_fields)| {
+            let project = projects.remove(&Uuid::parse_str(&id).ok()?)?;
+            Some(ProjectSearchResult {
+                project,
+                score,
+                matched_fields,
+            })
+        })
+        .collect())
+}
+
+pub(crate) fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Scores a single text between 0 and 1 the way a document field is scored.
+/// Every term has to match.
+pub(crate) fn text_score(terms: &[String], text: &str) -> Option<f64> {
+    let tokens = tokenize(text);
+    let lowered = text.to_lowercase();
+    let mut total = 0.0;
+    for term in terms {
+        let mut best = tokens
+            .iter()
+            .map(|token| term_score(term, token))
+            .fold(0.0, f64::max);
+        if best == 0.0 && is_subsequence(term, &lowered) {
+            best = SUBSEQUENCE_SCORE;
+        }
+        if best == 0.0 {
+            return None;
+        }
+        total += best;
+    }
+    Some(total / terms.len() as f64)
+}
+
+/// Scores a document between 0 and 1 and names the fields that matched.
+/// Every term has to match some field, possibly with a typo.
+fn fuzzy_score(terms: &[String], fields: [&String; 6]) -> Option<(f64, Vec<String>)> {
+    let tokens: Vec<Vec<String>> = fields.iter().map(|field| tokenize(field)).collect();
+    let mut total = 0.0;
+    let mut matched_fields = Vec::new();
+    for term in terms {
+        let mut best = 0.0;
+        let mut best_field = None;
+        for (index, field_tokens) in tokens.iter().enumerate() {
+            let score = field_tokens
+                .iter()
+                .map(|token| term_score(term, token))
+                .fold(0.0, f64::max)
+                * FIELDS[index].1;
+            if score > best {
+                best = score;
+                best_field = Some(index);
+            }
+        }
+        // Abbreviations such as `pm` for `pro-manager`
+        if best_field.is_none() && is_subsequence(term, &fields[0].to_lowercase()) {
+            best = SUBSEQUENCE_SCORE;
+            best_field = Some(0);
+        }
+        let name = FIELDS[best_field?].0.to_string();
+        if !matched_fields.contains(&name) {
+            matched_fields.push(name);
+        }
+        total += best;
+    }
+    Some((total / terms.len() as f64, matched_fields))
+}
+
+fn term_score(term: &str, token: &str) -> f64 {
+    if token == term {
+        return 1.0;
+    }
+    if token.starts_with(term) {
+        return 0.9;
+    }
+    if token.contains(term) {
+        return 0.7;
+    }
+    let length = term.chars().count();
+    let allowed = match length {
+        0..=3 => return 0.0,
+        4..=6 => 1,
+        _ => 2,
+    };
+    // Compare with the start of the token too, for typos while typing
+    let prefix: String = token.chars().take(length).collect();
+    let distance = edit_distance(term, token).min(edit_distance(term, &prefix));
+    if distance <= allowed {
+        0.6 - 0.15 * (distance as f64 - 1.0)
+    } else {
+        0.0
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle
+        .chars()
+        .all(|needle_char| haystack.any(|c| c == needle_char))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distance_counts_single_character_edits() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("react", "raect"), 2);
+        assert_eq!(edit_distance("crate", "crate"), 0);
+    }
+
+    #[test]
+    fn term_score_ranks_exact_prefix_substring_and_typos() {
+        assert_eq!(term_score("crate", "crate"), 1.0);
+        assert_eq!(term_score("cra", "crate"), 0.9);
+        assert_eq!(term_score("rat", "crate"), 0.7);
+        assert_eq!(term_score("crste", "crate"), 0.6);
+        assert_eq!(term_score("dashbord", "dashboard"), 0.6);
+        // Typos are only forgiven in terms long enough to tell apart
+        assert_eq!(term_score("cta", "crate"), 0.0);
+        assert_eq!(term_score("kotlin", "crate"), 0.0);
+    }
+
+    #[test]
+    fn term_score_forgives_typos_in_a_partly_typed_token() {
+        assert_eq!(term_score("dashbpa", "dashboard"), 0.6);
+        assert!((term_score("dashbaord", "dashboard") - 0.45).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,242 @@
+use std::process::ExitStatus;
+
+use chrono::Utc;
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, AppResult},
+    models::{LaunchSession, LaunchSessionRow, SessionStatus, SessionSummary},
+    project::parse_timestamp,
+    state::WorkspaceHandle,
+};
+
+const SESSION_SELECT: &str = r#"
+SELECT id, project_id, instance_id, process_name, config_id, status, started_at, ended_at, pid,
+       exit_code, signal, error
+FROM launch_sessions
+"#;
+
+/// Identifies what a launch session belongs to.
+pub struct SessionTarget<'a> {
+    pub project_id: Uuid,
+    pub instance_id: Uuid,
+    pub config_id: Uuid,
+    pub process_name: &'a str,
+}
+
+/// Opens a session for a launch. `pid` is `None` for launches handed to the
+/// system opener, which are closed straight away as detached.
+pub async fn open_session(
+    handle: &WorkspaceHandle,
+    target: &SessionTarget<'_>,
+    pid: Option<u32>,
+) -> AppResult<Uuid> {
+    let id = Uuid::new_v4();
+    let now = Utc::now().to_rfc3339();
+    let (status, ended_at) = match pid {
+        Some(_) => (SessionStatus::Running, None),
+        None => (SessionStatus::Detached, Some(now.clone())),
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO launch_sessions (
+          id, project_id, instance_id, process_name, config_id, status, started_at, ended_at, pid
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(target.project_id.to_string())
+    .bind(target.instance_id.to_string())
+    .bind(target.process_name)
+    .bind(target.config_id.to_string())
+    .bind(status.as_str())
+    .bind(&now)
+    .bind(ended_at)
+    .bind(pid.map(i64::from))
+    .execute(&handle.pool)
+    .await?;
+
+    Ok(id)
+}
+
+pub async fn record_spawn_failure(
+    handle: &WorkspaceHandle,
+    target: &SessionTarget<'_>,
+    error: &str,
+) -> AppResult<()> {
+    let now = Utc::now().to_rfc3339();
+    sqlx::query(
+        r#"
+        INSERT INTO launch_sessions (
+          id, project_id, instance_id, process_name, config_id, status, started_at, ended_at,
+          error
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(target.project_id.to_string())
+    .bind(target.instance_id.to_string())
+    .bind(target.process_name)
+    .bind(target.config_id.to_string())
+    .bind(SessionStatus::SpawnFailed.as_str())
+    .bind(&now)
+    .bind(&now)
+    .bind(error)
+    .execute(&handle.pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn close_session(
+    handle: &WorkspaceHandle,
+    session_id: Uuid,
+    status: SessionStatus,
+    exit_status: Option<ExitStatus>,
+) -> AppResult<()> {
+    let (exit_code, signal) = match exit_status {
+        Some(exit_status) => (exit_status.code(), exit_signal(&exit_status)),
+        None => (None, None),
+    };
+
+    sqlx::query(
+        r#"
+        UPDATE launch_sessions
+        SET status = ?, ended_at = ?, exit_code = ?, signal = ?
+        WHERE id = ? AND ended_at IS NULL
+        "#,
+    )
+    .bind(status.as_str())
+    .bind(Utc::now().to_rfc3339())
+    .bind(exit_code)
+    .bind(signal)
+    .bind(session_id.to_string())
+    .execute(&handle.pool)
+    .await?;
+    Ok(())
+}
+
+/// Closes sessions left open by a previous run of the app, whose processes
+/// are no longer tracked.
+pub async fn close_abandoned_sessions(pool: &sqlx::SqlitePool) -> AppResult<()> {
+    sqlx::query("UPDATE launch_sessions SET status = ?, ended_at = ? WHERE ended_at IS NULL")
+        .bind(SessionStatus::Abandoned.as_str())
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn list_sessions(
+    handle: &WorkspaceHandle,
+    project_id: Option<Uuid>,
+    instance_id: Option<Uuid>,
+    limit: u32,
+) -> AppResult<Vec<LaunchSession>> {
+    let rows = sqlx::query_as::<_, LaunchSessionRow>(&format!(
+        r#"{SESSION_SELECT}
+        WHERE (?1 IS NULL OR project_id = ?1) AND (?2 IS NULL OR instance_id = ?2)
+        ORDER BY started_at DESC
+        LIMIT ?3"#
+    ))
+    .bind(project_id.map(|id| id.to_string()))
+    .bind(instance_id.map(|id| id.to_string()))
+    .bind(limit)
+    .fetch_all(&handle.pool)
+    .await?;
+    rows.into_iter().map(row_to_session).collect()
+}
+
+pub async fn get_session_summaries(handle: &WorkspaceHandle) -> AppResult<Vec<SessionSummary>> {
+    let rows = sqlx::query(
+        r#"
+        SELECT
+          p.id,
+          p.name,
+          count(s.id) AS total_sessions,
+          count(CASE WHEN s.status = 'crashed' THEN 1 END) AS crashes,
+          count(CASE WHEN s.status = 'spawn_failed' THEN 1 END) AS spawn_failures,
+          avg(
+            CASE WHEN s.pid IS NOT NULL AND s.ended_at IS NOT NULL
+              THEN (julianday(s.ended_at) - julianday(s.started_at)) * 86400.0
+            END
+          ) AS average_duration_secs,
+          max(s.started_at) AS last_started_at
+        FROM projects p
+        JOIN launch_sessions s ON s.project_id = p.id
+        WHERE p.deleted_at IS NULL
+        GROUP BY p.id
+        ORDER BY crashes DESC, total_sessions DESC
+        "#,
+    )
+    .fetch_all(&handle.pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            let id: String = row.get("id");
+            let last_started_at: Option<String> = row.get("last_started_at");
+            Ok(SessionSummary {
+                project_id: parse_id(&id)?,
+                name: row.get("name"),
+                total_sessions: row.get("total_sessions"),
+                crashes: row.get("crashes"),
+                spawn_failures: row.get("spawn_failures"),
+                average_duration_secs: row.get("average_duration_secs"),
+                last_started_at: last_started_at
+                    .as_deref()
+                    .map(parse_timestamp)
+                    .transpose()?,
+            })
+        })
+        .collect()
+}
+
+/// Classifies a process that ended without being stopped by the user.
+pub fn status_from_exit(exit_status: Option<&ExitStatus>) -> SessionStatus {
+    match exit_status {
+        Some(exit_status) if exit_status.success() => SessionStatus::Exited,
+        _ => SessionStatus::Crashed,
+    }
+}
+
+#[cfg(unix)]
+fn exit_signal(exit_status: &ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    exit_status.signal()
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_exit_status: &ExitStatus) -> Option<i32> {
+    None
+}
+
+fn row_to_session(row: LaunchSessionRow) -> AppResult<LaunchSession> {
+    let status = SessionStatus::parse(&row.status)
+        .ok_or_else(|| AppError::Validation(format!("invalid session status '{}'", row.status)))?;
+    let started_at = parse_timestamp(&row.started_at)?;
+    let ended_at = row.ended_at.as_deref().map(parse_timestamp).transpose()?;
+    Ok(LaunchSession {
+        id: parse_id(&row.id)?,
+        project_id: parse_id(&row.project_id)?,
+        instance_id: row.instance_id.as_deref().map(parse_id).transpose()?,
+        process_name: row.process_name,
+        config_id: row.config_id.as_deref().map(parse_id).transpose()?,
+        status,
+        started_at,
+        ended_at,
+        duration_secs: ended_at.map(|end| (end - started_at).num_seconds()),
+        pid: row.pid.map(|pid| pid as u32),
+        exit_code: row.exit_code.map(|code| code as i32),
+        signal: row.signal.map(|signal| signal as i32),
+        error: row.error,
+    })
+}
+
+fn parse_id(value: &str) -> AppResult<Uuid> {
+    Uuid::parse_str(value)
+        .map_err(|err| AppError::Validation(format!("invalid id '{value}': {err}")))
+}
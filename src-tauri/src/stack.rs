@@ -0,0 +1,343 @@
+use std::{fs, path::Path};
+
+use serde_json::Value;
+
+use crate::{discovery::PackageManager, models::ProjectStack};
+
+/// Framework markers looked up in the dependencies of a manifest, paired
+/// with the name recorded on the project.
+const NODE_FRAMEWORKS: [(&str, &str); 15] = [
+    ("next", "next"),
+    ("nuxt", "nuxt"),
+    ("@remix-run/react", "remix"),
+    ("astro", "astro"),
+    ("@sveltejs/kit", "sveltekit"),
+    ("svelte", "svelte"),
+    ("@angular/core", "angular"),
+    ("vue", "vue"),
+    ("react", "react"),
+    ("solid-js", "solid"),
+    ("@nestjs/core", "nestjs"),
+    ("express", "express"),
+    ("electron", "electron"),
+    ("@tauri-apps/api", "tauri"),
+    ("vite", "vite"),
+];
+const RUST_FRAMEWORKS: [(&str, &str); 6] = [
+    ("tauri", "tauri"),
+    ("axum", "axum"),
+    ("actix-web", "actix-web"),
+    ("rocket", "rocket"),
+    ("leptos", "leptos"),
+    ("bevy", "bevy"),
+];
+const PYTHON_FRAMEWORKS: [(&str, &str); 5] = [
+    ("django", "django"),
+    ("fastapi", "fastapi"),
+    ("flask", "flask"),
+    ("streamlit", "streamlit"),
+    ("torch", "pytorch"),
+];
+const GO_FRAMEWORKS: [(&str, &str); 4] = [
+    ("github.com/gin-gonic/gin", "gin"),
+    ("github.com/labstack/echo", "echo"),
+    ("github.com/gofiber/fiber", "fiber"),
+    ("github.com/spf13/cobra", "cobra"),
+];
+
+/// Inspects the marker files at the root of `path`. Stacks are checked from
+/// the most to the least specific, so that a `package.json` used for tooling
+/// does not make a Django or Spring Boot project count as JavaScript.
+pub fn detect(path: &Path) -> ProjectStack {
+    let mut stack = ProjectStack::default();
+    let has = |name: &str| path.join(name).is_file();
+    let read = |name: &str| fs::read_to_string(path.join(name)).unwrap_or_default();
+
+    if has("Cargo.toml") {
+        let manifest = read("Cargo.toml");
+        stack.add_language("rust");
+        stack.add_build_system("cargo");
+        stack.add_frameworks(&manifest, &RUST_FRAMEWORKS);
+    }
+    if has("go.mod") {
+        stack.add_language("go");
+        stack.add_build_system("go");
+        stack.add_frameworks(&read("go.mod"), &GO_FRAMEWORKS);
+    }
+    if has("pom.xml") {
+        let pom = read("pom.xml");
+        let language = if pom.contains("kotlin-maven-plugin") {
+            "kotlin"
+        } else {
+            "java"
+        };
+        stack.add_language(language);
+        stack.add_build_system("maven");
+        if pom.contains("spring-boot") {
+            stack.add_framework("spring-boot");
+        }
+    }
+    if has("build.gradle") || has("build.gradle.kts") {
+        let build = read("build.gradle") + &read("build.gradle.kts");
+        let language = if has("build.gradle.kts") || build.contains("org.jetbrains.kotlin") {
+            "kotlin"
+        } else {
+            "java"
+        };
+        stack.add_language(language);
+        stack.add_build_system("gradle");
+        if build.contains("org.springframework.boot") {
+            stack.add_framework("spring-boot");
+        }
+        if build.contains("com.android") {
+            stack.add_framework("android");
+        }
+    }
+    if has("CMakeLists.txt") {
+        let cmake = read("CMakeLists.txt");
+        let is_c_only = cmake.contains("LANGUAGES C") && !cmake.contains("CXX");
+        stack.add_language(if is_c_only { "c" } else { "c++" });
+        stack.add_build_system("cmake");
+        if cmake.contains("Qt5") || cmake.contains("Qt6") {
+            stack.add_framework("qt");
+        }
+    }
+    if has("meson.build") {
+        stack.add_language("c++");
+        stack.add_build_system("meson");
+    }
+    if has("pyproject.toml") || has("requirements.txt") || has("setup.py") || has("Pipfile") {
+        stack.add_language("python");
+        let uses_poetry = has("poetry.lock") || read("pyproject.toml").contains("[tool.poetry");
+        let build_system = if uses_poetry {
+            "poetry"
+        } else if has("uv.lock") {
+            "uv"
+        } else if has("pdm.lock") {
+            "pdm"
+        } else if has("Pipfile") {
+            "pipenv"
+        } else if has("pyproject.toml") {
+            "pip"
+        } else {
+            "setuptools"
+        };
+        stack.add_build_system(build_system);
+        let dependencies = ["pyproject.toml", "requirements.txt", "Pipfile", "setup.py"]
+            .iter()
+            .map(|name| read(name))
+            .collect::<String>()
+            .to_lowercase();
+        stack.add_frameworks(&dependencies, &PYTHON_FRAMEWORKS);
+    }
+    if has("Gemfile") {
+        stack.add_language("ruby");
+        stack.add_build_system("bundler");
+        if read("Gemfile").contains("rails") {
+            stack.add_framework("rails");
+        }
+    }
+    if has("composer.json") {
+        let composer = read("composer.json");
+        stack.add_language("php");
+        stack.add_build_system("composer");
+        if composer.contains("laravel/framework") {
+            stack.add_framework("laravel");
+        } else if composer.contains("symfony/") {
+            stack.add_framework("symfony");
+        }
+    }
+    if has("mix.exs") {
+        stack.add_language("elixir");
+        stack.add_build_system("mix");
+        if read("mix.exs").contains(":phoenix") {
+            stack.add_framework("phoenix");
+        }
+    }
+    if has("pubspec.yaml") {
+        stack.add_language("dart");
+        if read("pubspec.yaml").contains("flutter:") {
+            stack.add_framework("flutter");
+            stack.add_build_system("flutter");
+        } else {
+            stack.add_build_system("pub");
+        }
+    }
+    if has("Package.swift") {
+        stack.add_language("swift");
+        stack.add_build_system("swiftpm");
+    }
+    if has_extension(path, &["sln", "csproj", "fsproj"]) {
+        stack.add_language("c#");
+        stack.add_build_system("dotnet");
+    }
+    if has("deno.json") || has("deno.jsonc") {
+        stack.add_language("typescript");
+        stack.add_build_system("deno");
+    }
+    if has("package.json") {
+        detect_node(path, &read("package.json"), &mut stack);
+    }
+    if stack.build_systems.is_empty() && (has("Makefile") || has("makefile")) {
+        stack.add_build_system("make");
+    }
+    stack
+}
+
+fn detect_node(path: &Path, contents: &str, stack: &mut ProjectStack) {
+    let manifest: Value = match serde_json::from_str(contents) {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            log::warn!("failed to parse package.json in {}: {err}", path.display());
+            return;
+        }
+    };
+    let has_dependency = |name: &str| {
+        ["dependencies", "devDependencies", "peerDependencies"]
+            .iter()
+            .any(|section| {
+                manifest
+                    .get(section)
+                    .and_then(|deps| deps.get(name))
+                    .is_some()
+            })
+    };
+
+    let typescript = path.join("tsconfig.json").is_file() || has_dependency("typescript");
+    let language = if typescript {
+        "typescript"
+    } else {
+        "javascript"
+    };
+    stack.add_language(language);
+    stack.add_build_system(PackageManager::detect(path, &manifest).command());
+    for (dependency, framework) in NODE_FRAMEWORKS {
+        if has_dependency(dependency) {
+            stack.add_framework(framework);
+        }
+    }
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    let Ok(entries) = fs::read_dir(path) else {
+        return false;
+    };
+    entries.filter_map(Result::ok).any(|entry| {
+        entry
+            .path()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| extensions.contains(&ext))
+    })
+}
+
+impl ProjectStack {
+    fn add_language(&mut self, language: &str) {
+        if self.primary_language.is_none() {
+            self.primary_language = Some(language.to_string());
+        }
+        push_unique(&mut self.languages, language);
+    }
+
+    fn add_build_system(&mut self, build_system: &str) {
+        push_unique(&mut self.build_systems, build_system);
+    }
+
+    fn add_framework(&mut self, framework: &str) {
+        push_unique(&mut self.frameworks, framework);
+    }
+
+    /// Adds the frameworks whose marker appears as a dependency name in
+    /// `manifest`. Subpaths such as Go major versions (`echo/v4`) count.
+    fn add_frameworks(&mut self, manifest: &str, markers: &[(&str, &str)]) {
+        let punctuation = [
+            '=', '"', '\'', '[', ']', '{', '}', '<', '>', '~', '^', ',', ':', '!',
+        ];
+        let names: Vec<&str> = manifest
+            .split(|c: char| c.is_whitespace() || punctuation.contains(&c))
+            .filter(|name| !name.is_empty())
+            .collect();
+        for (marker, framework) in markers {
+            let listed = names.iter().any(|name| {
+                name == marker
+                    || name
+                        .strip_prefix(marker)
+                        .is_some_and(|rest| rest.starts_with('/'))
+            });
+            if listed {
+                self.add_framework(framework);
+            }
+        }
+    }
+}
+
+fn push_unique(values: &mut Vec<String>, value: &str) {
+    if !values.iter().any(|existing| existing == value) {
+        values.push(value.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect_files(files: &[(&str, &str)]) -> ProjectStack {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, contents) in files {
+            fs::write(dir.path().join(name), contents).unwrap();
+        }
+        detect(dir.path())
+    }
+
+    #[test]
+    fn frameworks_are_found_on_their_own_manifest_lines() {
+        let stack = detect_files(&[(
+            "Cargo.toml",
+            "[package]\nname = \"app\"\n\n[dependencies]\naxum = \"0.7\"\ntauri = { version = \"2\" }\ntauri-build = \"2\"\n",
+        )]);
+        assert_eq!(stack.primary_language.as_deref(), Some("rust"));
+        assert_eq!(stack.build_systems, ["cargo"]);
+        assert_eq!(stack.frameworks, ["tauri", "axum"]);
+
+        let stack = detect_files(&[("requirements.txt", "Django>=4.2\nflask-cors\ntorch\n")]);
+        assert_eq!(stack.build_systems, ["setuptools"]);
+        assert_eq!(stack.frameworks, ["django", "pytorch"]);
+    }
+
+    #[test]
+    fn go_frameworks_match_major_version_subpaths() {
+        let stack = detect_files(&[(
+            "go.mod",
+            "module example.com/app\n\nrequire (\n\tgithub.com/labstack/echo/v4 v4.11.0\n\tgithub.com/gin-gonic/gin-contrib v0.1.0\n)\n",
+        )]);
+        assert_eq!(stack.frameworks, ["echo"]);
+    }
+
+    #[test]
+    fn more_specific_stacks_come_before_tooling_package_json() {
+        let stack = detect_files(&[
+            (
+                "pyproject.toml",
+                "[tool.poetry.dependencies]\ndjango = \"^5\"\n",
+            ),
+            (
+                "package.json",
+                r#"{"devDependencies": {"vite": "5", "typescript": "5"}}"#,
+            ),
+        ]);
+        assert_eq!(stack.primary_language.as_deref(), Some("python"));
+        assert_eq!(stack.languages, ["python", "typescript"]);
+        assert_eq!(stack.build_systems, ["poetry", "npm"]);
+        assert_eq!(stack.frameworks, ["django", "vite"]);
+    }
+
+    #[test]
+    fn makefile_only_counts_without_another_build_system() {
+        assert_eq!(
+            detect_files(&[("Makefile", "all:\n")]).build_systems,
+            ["make"]
+        );
+        let stack = detect_files(&[("go.mod", "module app\n"), ("Makefile", "all:\n")]);
+        assert_eq!(stack.build_systems, ["go"]);
+    }
+}
@@ -1,34 +1,70 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
     fs,
     path::{Path, PathBuf},
-    sync::Arc,
+    process::ExitStatus,
+    sync::{Arc, PoisonError, Weak},
+    time::{Duration, Instant},
 };
 
 use chrono::{DateTime, Utc};
+use notify::{event::ModifyKind, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use serde::{Deserialize, Serialize};
 use sqlx::{
     sqlite::{SqliteConnectOptions, SqlitePoolOptions},
     SqlitePool,
 };
-use tokio::sync::RwLock;
+use tauri::{AppHandle, Emitter};
+use tokio::{
+    sync::{Mutex, RwLock},
+    task::JoinSet,
+};
 use uuid::Uuid;
 
 use crate::{
     db::{apply_default_pragmas, init_workspace_schema},
     error::{AppError, AppResult},
+    git, health, integrity,
     models::{
-        AppSettings, AppSettingsUpdate, LaunchPreset, LaunchPresetInput, ThemePreference,
-        WorkspaceInput, WorkspaceRecord,
+        default_watch_depth, AppSettings, AppSettingsUpdate, CrashLoopEvent, LaunchConfig,
+        LaunchPreset, LaunchPresetInput, OpenConfig, PathHealth, PathHealthEvent, PresetReference,
+        PresetUsage, ProcessInstance, ProcessRestartEvent, ProcessStopResult, Project,
+        ProjectInput, ProjectPathHealth, RestartMode, RestartPolicy, ScanCandidate, SessionStatus,
+        StopOutcome, ThemePreference, WatchedFolder, WatchedFolderInput, WorkspaceCheck,
+        WorkspaceInput, WorkspaceRecord, WorkspaceRepair, WorkspaceStatus,
     },
-    project,
+    presets,
+    process::{self, RunningProcess, SharedOutput},
+    project, scan, session, watch,
 };
 
 const CONFIG_FILENAME: &str = "workspaces.json";
+const SUPERVISOR_INTERVAL: Duration = Duration::from_secs(1);
+const WATCH_DEBOUNCE: Duration = Duration::from_secs(2);
+/// Longest a burst of events is collected before it is processed.
+const MAX_WATCH_DEBOUNCE: Duration = Duration::from_secs(10);
+const MAX_WATCH_DEPTH: usize = 6;
+const PATH_HEALTH_INTERVAL: Duration = Duration::from_secs(60);
+/// Connections a single workspace pool may open.
+const POOL_MAX_CONNECTIONS: u32 = 4;
+/// Connections the pools of all workspaces may hold together.
+const MAX_OPEN_CONNECTIONS: u32 = 16;
+/// Pools unused for this long are closed, except the active workspace's.
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+const POOL_EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+
+pub const RESTART_EVENT: &str = "process-restarting";
+pub const CRASH_LOOP_EVENT: &str = "process-crash-loop";
 
 #[derive(Debug, Clone)]
 pub struct AppState {
     pub inner: Arc<RwLock<AppStateInner>>,
+    /// Serialises opening workspace databases, so that no database is
+    /// migrated twice at once.
+    opening: Arc<Mutex<()>>,
+    /// Serialises changes to the launch presets with project saves, so that
+    /// no project starts referencing a preset while it is being removed.
+    presets: Arc<Mutex<()>>,
 }
 
 #[derive(Debug)]
@@ -36,8 +72,79 @@ pub struct AppStateInner {
     base_dir: PathBuf,
     config_path: PathBuf,
     config: AppConfig,
-    workspace_pools: HashMap<Uuid, SqlitePool>,
-    pub running_processes: HashMap<Uuid, std::process::Child>,
+    /// Pools of the workspaces used recently. Others are opened on demand by
+    /// [`AppState::workspace_handle`].
+    workspace_pools: HashMap<Uuid, OpenPool>,
+    /// Leases of the single-connection pools opened on workspaces that failed
+    /// to open, which count toward [`MAX_OPEN_CONNECTIONS`] while held.
+    unavailable_leases: Vec<Weak<()>>,
+    /// Why a workspace database failed to open, by workspace id. Such
+    /// workspaces stay listed so they can be reopened, relocated or deleted.
+    workspace_errors: HashMap<Uuid, String>,
+    /// Workspaces opened since the app started. Sessions left open are only
+    /// closed as abandoned the first time, as later they may be running.
+    opened_workspaces: HashSet<Uuid>,
+    pub running_processes: HashMap<Uuid, RunningProcess>,
+    /// Project id and name of instances being launched, so that concurrent
+    /// launches cannot start the same named instance twice.
+    launching: HashSet<(Uuid, String)>,
+    /// Project id and name of instances whose process group is shutting
+    /// down, whose names cannot be reused until it is gone.
+    stopping: HashSet<(Uuid, String)>,
+    pub process_output: HashMap<Uuid, SharedOutput>,
+    pub supervisor: Supervisor,
+    folder_watcher: Option<RecommendedWatcher>,
+    /// Directories the folder watcher has a watch on.
+    watched_paths: HashSet<PathBuf>,
+    git_statuses: git::StatusCache,
+    /// Last known path health by project id, to report only changes.
+    path_health: HashMap<Uuid, PathHealth>,
+}
+
+/// Schedules restarts of tracked processes according to their project's
+/// [`RestartPolicy`]. Decisions are made while reaping; the background loop
+/// in [`AppState::run_supervisor`] performs the launches and emits events.
+/// Trackers are keyed by process instance id.
+#[derive(Debug, Default)]
+pub struct Supervisor {
+    restarts: HashMap<Uuid, RestartTracker>,
+    notices: Vec<SupervisorNotice>,
+}
+
+#[derive(Debug)]
+struct RestartTracker {
+    project_id: Uuid,
+    workspace_id: Uuid,
+    config_id: Uuid,
+    name: String,
+    policy: RestartPolicy,
+    history: VecDeque<Instant>,
+    due: Option<Instant>,
+}
+
+#[derive(Debug)]
+struct DueRestart {
+    instance_id: Uuid,
+    project_id: Uuid,
+    workspace_id: Uuid,
+    config_id: Uuid,
+    name: String,
+}
+
+/// Options for [`AppState::launch_tracked`]. Unset fields fall back to the
+/// project's default launch configuration, that configuration's name and a
+/// fresh instance id.
+#[derive(Debug, Default)]
+pub struct LaunchRequest {
+    pub config_id: Option<Uuid>,
+    pub name: Option<String>,
+    pub instance_id: Option<Uuid>,
+}
+
+#[derive(Debug)]
+enum SupervisorNotice {
+    Restarting(ProcessRestartEvent),
+    CrashLoop(CrashLoopEvent),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -76,23 +183,36 @@ impl AppState {
             config_changed = true;
         }
 
+        // Only the active workspace is opened up front, the others on first use
         let mut workspace_pools = HashMap::new();
-        for workspace in &config.workspaces {
-            if let Some(parent) = workspace.database_path.parent() {
-                if !parent.exists() {
-                    fs::create_dir_all(parent)?;
+        let mut workspace_errors = HashMap::new();
+        let mut opened_workspaces = HashSet::new();
+        let active = config
+            .active_workspace_id
+            .and_then(|id| config.find_workspace(id));
+        if let Some(workspace) = active {
+            // Only the workspace bootstrapped with a new config has no database yet
+            let opened = open_workspace_pool(
+                &workspace.database_path,
+                created_new_config,
+                true,
+                config.settings.trash_retention_days,
+            )
+            .await;
+            match opened {
+                Ok(pool) => {
+                    workspace_pools.insert(workspace.id, OpenPool::new(pool));
+                    opened_workspaces.insert(workspace.id);
+                }
+                Err(err) => {
+                    log::error!(
+                        "failed to open workspace '{}' at {}: {err}",
+                        workspace.name,
+                        workspace.database_path.display()
+                    );
+                    workspace_errors.insert(workspace.id, err.to_string());
                 }
             }
-
-            let options = apply_default_pragmas(
-                SqliteConnectOptions::new().filename(&workspace.database_path),
-            );
-            let pool = SqlitePoolOptions::new()
-                .max_connections(5)
-                .connect_with(options)
-                .await?;
-            init_workspace_schema(&pool).await?;
-            workspace_pools.insert(workspace.id, pool);
         }
 
         let inner = AppStateInner {
@@ -100,10 +220,23 @@ impl AppState {
             config_path,
             config,
             workspace_pools,
+            unavailable_leases: Vec::new(),
+            workspace_errors,
+            opened_workspaces,
             running_processes: HashMap::new(),
+            launching: HashSet::new(),
+            stopping: HashSet::new(),
+            process_output: HashMap::new(),
+            supervisor: Supervisor::default(),
+            folder_watcher: None,
+            watched_paths: HashSet::new(),
+            git_statuses: git::StatusCache::default(),
+            path_health: HashMap::new(),
         };
         let state = Self {
             inner: Arc::new(RwLock::new(inner)),
+            opening: Arc::new(Mutex::new(())),
+            presets: Arc::new(Mutex::new(())),
         };
 
         if config_changed {
@@ -133,11 +266,18 @@ impl AppState {
         }
         inner.config.active_workspace_id = Some(workspace_id);
         inner.persist_config()?;
-        Ok(inner
+        let record = inner
             .config
             .find_workspace(workspace_id)
             .expect("workspace checked above")
-            .clone())
+            .clone();
+        drop(inner);
+
+        // Pre-warm the pool; a failure shows once the workspace is used
+        if let Err(err) = self.workspace_handle(Some(workspace_id)).await {
+            log::warn!("failed to open workspace '{}': {err}", record.name);
+        }
+        Ok(record)
     }
 
     pub async fn rename_workspace(&self, id: Uuid, new_name: String) -> AppResult<WorkspaceRecord> {
@@ -181,6 +321,8 @@ impl AppState {
     }
 
     pub async fn delete_workspace(&self, id: Uuid) -> AppResult<()> {
+        // Waits for an open of this workspace to finish, so its pool is closed
+        let _opening = self.opening.lock().await;
         let mut inner = self.inner.write().await;
 
         if inner.config.workspaces.len() <= 1 {
@@ -212,9 +354,11 @@ impl AppState {
         }
 
         // Remove pool
-        if let Some(pool) = inner.workspace_pools.remove(&id) {
-             pool.close().await;
+        if let Some(open) = inner.workspace_pools.remove(&id) {
+            open.pool.close().await;
         }
+        inner.workspace_errors.remove(&id);
+        inner.opened_workspaces.remove(&id);
 
         // Persist config first
         inner.persist_config()?;
@@ -258,19 +402,13 @@ impl AppState {
             None => inner.default_workspace_db_path(&id),
         };
 
-        if let Some(parent) = database_path.parent() {
-            if !parent.exists() {
-                fs::create_dir_all(parent)?;
-            }
-        }
-
-        let options =
-            apply_default_pragmas(SqliteConnectOptions::new().filename(database_path.as_path()));
-        let pool = SqlitePoolOptions::new()
-            .max_connections(5)
-            .connect_with(options)
-            .await?;
-        init_workspace_schema(&pool).await?;
+        let pool = open_workspace_pool(
+            &database_path,
+            true,
+            true,
+            inner.config.settings.trash_retention_days,
+        )
+        .await?;
 
         let record = WorkspaceRecord {
             id,
@@ -279,10 +417,11 @@ impl AppState {
             database_path: database_path.clone(),
             created_at: now,
             updated_at: now,
+            watched_folders: Vec::new(),
         };
 
         inner.config.workspaces.push(record.clone());
-        inner.workspace_pools.insert(id, pool);
+        inner.insert_pool(record.clone(), pool);
         if inner.config.active_workspace_id.is_none() {
             inner.config.active_workspace_id = Some(id);
         }
@@ -290,28 +429,984 @@ impl AppState {
         Ok(record)
     }
 
+    /// Resolves a workspace, opening its database on first use. A workspace
+    /// that failed to open stays unavailable until it is reopened.
     pub async fn workspace_handle(&self, workspace_id: Option<Uuid>) -> AppResult<WorkspaceHandle> {
+        if let Some(handle) = self.open_handle(workspace_id).await? {
+            return Ok(handle);
+        }
+        let _opening = self.opening.lock().await;
+        // Opened by another task while waiting
+        if let Some(handle) = self.open_handle(workspace_id).await? {
+            return Ok(handle);
+        }
+
+        let (meta, first_open, retention_days) = {
+            let inner = self.inner.read().await;
+            let meta = inner.find_workspace(workspace_id)?.clone();
+            let first_open = !inner.opened_workspaces.contains(&meta.id);
+            (meta, first_open, inner.config.settings.trash_retention_days)
+        };
+        // Migrations may take a while, so the state is not locked meanwhile
+        let opened =
+            open_workspace_pool(&meta.database_path, false, first_open, retention_days).await;
+        let mut inner = self.inner.write().await;
+        // Deleted while its database was being opened
+        if inner.config.find_workspace(meta.id).is_none() {
+            if let Ok(pool) = opened {
+                pool.close().await;
+            }
+            return Err(AppError::WorkspaceNotFound(meta.id.to_string()));
+        }
+        match opened {
+            Ok(pool) => Ok(inner.insert_pool(meta, pool)),
+            Err(err) => {
+                log::error!("failed to open workspace '{}': {err}", meta.name);
+                inner.workspace_errors.insert(meta.id, err.to_string());
+                Err(err)
+            }
+        }
+    }
+
+    /// The handle of a workspace whose database is open, `None` if it is
+    /// closed.
+    async fn open_handle(&self, workspace_id: Option<Uuid>) -> AppResult<Option<WorkspaceHandle>> {
         let inner = self.inner.read().await;
-        let id = match workspace_id {
-            Some(id) => id,
-            None => inner
+        let meta = inner.find_workspace(workspace_id)?;
+        if let Some(reason) = inner.workspace_errors.get(&meta.id) {
+            return Err(AppError::WorkspaceUnavailable(
+                meta.name.clone(),
+                reason.clone(),
+            ));
+        }
+        Ok(inner.workspace_pools.get(&meta.id).map(|open| {
+            open.touch();
+            open.handle(meta.clone())
+        }))
+    }
+
+    /// Ids of every workspace, for work that visits them one at a time.
+    /// Only one handle should be held at once, so that pools of visited
+    /// workspaces can be evicted to stay within [`MAX_OPEN_CONNECTIONS`].
+    async fn workspace_ids(&self) -> Vec<Uuid> {
+        let inner = self.inner.read().await;
+        inner.config.workspaces.iter().map(|ws| ws.id).collect()
+    }
+
+    /// Launch configurations referencing a preset, across all workspaces.
+    /// Fails if a workspace cannot be opened, as its references would be
+    /// missed.
+    async fn collect_preset_references(&self) -> AppResult<Vec<(Uuid, PresetReference)>> {
+        let mut references = Vec::new();
+        for id in self.workspace_ids().await {
+            let handle = self.workspace_handle(Some(id)).await?;
+            references.extend(presets::find_references(&handle).await?);
+        }
+        Ok(references)
+    }
+
+    pub async fn workspace_statuses(&self) -> Vec<WorkspaceStatus> {
+        let inner = self.inner.read().await;
+        inner
+            .config
+            .workspaces
+            .iter()
+            .map(|workspace| WorkspaceStatus {
+                workspace_id: workspace.id,
+                available: !inner.workspace_errors.contains_key(&workspace.id),
+                open: inner.workspace_pools.contains_key(&workspace.id),
+                error: inner.workspace_errors.get(&workspace.id).cloned(),
+            })
+            .collect()
+    }
+
+    /// Tries again to open the database of a workspace that failed to open,
+    /// e.g. once it is no longer locked or its volume is mounted again.
+    pub async fn reopen_workspace(&self, id: Uuid) -> AppResult<WorkspaceStatus> {
+        {
+            let mut inner = self.inner.write().await;
+            if inner.config.find_workspace(id).is_none() {
+                return Err(AppError::WorkspaceNotFound(id.to_string()));
+            }
+            inner.workspace_errors.remove(&id);
+        }
+        self.workspace_handle(Some(id)).await?;
+        Ok(WorkspaceStatus {
+            workspace_id: id,
+            available: true,
+            open: true,
+            error: None,
+        })
+    }
+
+    /// Checks a workspace database. One that failed to open is checked as it
+    /// is on disk.
+    pub async fn check_workspace(&self, workspace_id: Option<Uuid>) -> AppResult<WorkspaceCheck> {
+        let handle = match self.workspace_handle(workspace_id).await {
+            Ok(handle) => return integrity::check_workspace(&handle).await,
+            Err(err) => match self.unavailable_handle(workspace_id).await? {
+                Some(handle) => handle,
+                None => return Err(err),
+            },
+        };
+        let check = integrity::check_workspace(&handle).await;
+        handle.pool.close().await;
+        check
+    }
+
+    /// Repairs a workspace database. One that failed to open is repaired as
+    /// it is on disk, after which opening it is tried again.
+    pub async fn repair_workspace(&self, workspace_id: Option<Uuid>) -> AppResult<WorkspaceRepair> {
+        let open_err = match self.workspace_handle(workspace_id).await {
+            Ok(handle) => return integrity::repair_workspace(&handle).await,
+            Err(err) => err,
+        };
+        let repair = {
+            let _opening = self.opening.lock().await;
+            let Some(handle) = self.unavailable_handle(workspace_id).await? else {
+                return Err(open_err);
+            };
+            let repair = integrity::repair_workspace(&handle).await;
+            handle.pool.close().await;
+            repair?
+        };
+        // The workspace stays unavailable with the new error if this fails
+        if let Err(err) = self.reopen_workspace(repair.workspace_id).await {
+            log::warn!(
+                "workspace {} still fails to open: {err}",
+                repair.workspace_id
+            );
+        }
+        Ok(repair)
+    }
+
+    /// Opens the database of a workspace that failed to open as it is: no
+    /// migrations, session cleanup or trash expiry, which may be what failed.
+    /// `None` if the workspace is not in that state.
+    async fn unavailable_handle(
+        &self,
+        workspace_id: Option<Uuid>,
+    ) -> AppResult<Option<WorkspaceHandle>> {
+        let meta = {
+            let inner = self.inner.read().await;
+            let meta = inner.find_workspace(workspace_id)?;
+            if !inner.workspace_errors.contains_key(&meta.id) {
+                return Ok(None);
+            }
+            meta.clone()
+        };
+        if !meta.database_path.is_file() {
+            return Err(AppError::Validation(format!(
+                "workspace database is missing: {}",
+                meta.database_path.display()
+            )));
+        }
+        let lease = Arc::new(());
+        {
+            let mut inner = self.inner.write().await;
+            inner.unavailable_leases.push(Arc::downgrade(&lease));
+            inner.evict_pools(None);
+        }
+        let options =
+            apply_default_pragmas(SqliteConnectOptions::new().filename(&meta.database_path))
+                .create_if_missing(false);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await?;
+        Ok(Some(WorkspaceHandle {
+            meta,
+            pool,
+            _lease: lease,
+        }))
+    }
+
+    /// Replaces the database of a workspace that failed to open with an
+    /// empty one. The old file is kept next to it as `<file>.broken-<time>`.
+    pub async fn recreate_workspace_database(&self, id: Uuid) -> AppResult<WorkspaceStatus> {
+        let _opening = self.opening.lock().await;
+        let (database_path, retention_days) = {
+            let inner = self.inner.read().await;
+            let Some(workspace) = inner.config.find_workspace(id) else {
+                return Err(AppError::WorkspaceNotFound(id.to_string()));
+            };
+            if !inner.workspace_errors.contains_key(&id) {
+                return Err(AppError::Validation(format!(
+                    "workspace '{}' opens fine, its database is left alone",
+                    workspace.name
+                )));
+            }
+            (
+                workspace.database_path.clone(),
+                inner.config.settings.trash_retention_days,
+            )
+        };
+
+        let suffix = format!("broken-{}", Utc::now().format("%Y%m%d%H%M%S"));
+        // A stale WAL would be replayed into the new database
+        for extension in ["", "-wal", "-shm"] {
+            let mut file = database_path.clone().into_os_string();
+            file.push(extension);
+            let file = PathBuf::from(file);
+            if file.exists() {
+                let mut aside = file.clone().into_os_string();
+                aside.push(format!(".{suffix}"));
+                fs::rename(&file, aside)?;
+            }
+        }
+        let pool = open_workspace_pool(&database_path, true, true, retention_days).await?;
+
+        let mut inner = self.inner.write().await;
+        let Some(meta) = inner.config.find_workspace(id).cloned() else {
+            pool.close().await;
+            return Err(AppError::WorkspaceNotFound(id.to_string()));
+        };
+        inner.insert_pool(meta, pool);
+        Ok(WorkspaceStatus {
+            workspace_id: id,
+            available: true,
+            open: true,
+            error: None,
+        })
+    }
+
+    /// Points a workspace at a database file in another location, such as
+    /// one that was moved or a backup taken before a migration. The
+    /// workspace is left unchanged if that database cannot be opened.
+    pub async fn relocate_workspace(
+        &self,
+        id: Uuid,
+        database_path: PathBuf,
+    ) -> AppResult<WorkspaceRecord> {
+        let database_path = project::sanitize_path_buf(database_path);
+        if !database_path.is_file() {
+            return Err(AppError::Validation(format!(
+                "workspace database does not exist: {}",
+                database_path.display()
+            )));
+        }
+
+        let _opening = self.opening.lock().await;
+        let (first_open, retention_days) = {
+            let inner = self.inner.read().await;
+            if inner.config.find_workspace(id).is_none() {
+                return Err(AppError::WorkspaceNotFound(id.to_string()));
+            }
+            (
+                !inner.opened_workspaces.contains(&id),
+                inner.config.settings.trash_retention_days,
+            )
+        };
+        let pool = open_workspace_pool(&database_path, false, first_open, retention_days).await?;
+
+        let mut inner = self.inner.write().await;
+        let record = {
+            let Some(workspace) = inner.config.workspaces.iter_mut().find(|ws| ws.id == id) else {
+                pool.close().await;
+                return Err(AppError::WorkspaceNotFound(id.to_string()));
+            };
+            workspace.database_path = database_path;
+            workspace.updated_at = Utc::now();
+            workspace.clone()
+        };
+        if let Some(previous) = inner.workspace_pools.remove(&id) {
+            previous.pool.close().await;
+        }
+        inner.insert_pool(record.clone(), pool);
+        inner.persist_config()?;
+        Ok(record)
+    }
+
+    /// Closes workspace pools that have not been used for a while.
+    pub async fn run_pool_eviction(self) {
+        let mut interval = tokio::time::interval(POOL_EVICTION_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.inner
+                .write()
+                .await
+                .evict_pools(Some(POOL_IDLE_TIMEOUT));
+        }
+    }
+
+    /// Drops tracked processes that have exited and closes their launch
+    /// sessions. Returns the instances that are still running.
+    pub async fn reap_processes(&self) -> Vec<ProcessInstance> {
+        let mut running = Vec::new();
+        let mut finished = Vec::new();
+        {
+            let mut inner = self.inner.write().await;
+            let mut exited = Vec::new();
+            for (id, entry) in inner.running_processes.iter_mut() {
+                match entry.child.try_wait() {
+                    Ok(Some(status)) => exited.push((*id, Some(status))),
+                    Ok(None) => running.push(entry.instance.clone()),
+                    Err(_) => exited.push((*id, None)),
+                }
+            }
+            for (id, status) in exited {
+                if let Some(entry) = inner.running_processes.remove(&id) {
+                    inner.supervisor.on_exit(&entry, status.as_ref());
+                    finished.push((entry, status));
+                }
+            }
+        }
+
+        for (entry, status) in finished {
+            let session_id = entry.instance.session_id;
+            let workspace_id = entry.instance.workspace_id;
+            let result = match self.workspace_handle(Some(workspace_id)).await {
+                Ok(handle) => {
+                    session::close_session(
+                        &handle,
+                        session_id,
+                        session::status_from_exit(status.as_ref()),
+                        status,
+                    )
+                    .await
+                }
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                log::warn!("failed to close session {session_id}: {err}");
+            }
+        }
+
+        running
+    }
+
+    /// Launches a project and, for tracked processes, captures its output and
+    /// registers it in `running_processes` as a new instance. Restarts pass
+    /// the `instance_id` they replace so it stays stable across runs.
+    pub async fn launch_tracked(
+        &self,
+        app: &AppHandle,
+        handle: &WorkspaceHandle,
+        project: &Project,
+        request: LaunchRequest,
+    ) -> AppResult<Option<ProcessInstance>> {
+        let settings = self.get_settings().await;
+        let launch_config = project::resolve_launch_config(project, request.config_id)?;
+        // Presets are looked up now so edits to them apply to the next launch
+        let launch_config = LaunchConfig {
+            config: presets::resolve(&launch_config.config, &settings.launch_presets)?,
+            ..launch_config.clone()
+        };
+        let name = request
+            .name
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| launch_config.name.clone());
+
+        // The name stays reserved until the instance is tracked
+        let reservation = (project.id, name.clone());
+        {
+            let mut inner = self.inner.write().await;
+            let duplicate = inner.launching.contains(&reservation)
+                || inner.running_processes.values().any(|entry| {
+                    entry.instance.project_id == project.id && entry.instance.name == name
+                });
+            if duplicate {
+                return Err(AppError::Validation(format!(
+                    "process '{name}' is already running for project '{}'",
+                    project.name
+                )));
+            }
+            if inner.stopping.contains(&reservation) {
+                return Err(AppError::Validation(format!(
+                    "process '{name}' of project '{}' is still stopping",
+                    project.name
+                )));
+            }
+            inner.launching.insert(reservation.clone());
+        }
+
+        let instance_id = request.instance_id.unwrap_or_else(Uuid::new_v4);
+        let launched =
+            project::launch_project(handle, project, &launch_config, instance_id, &name).await;
+        let launched = match launched {
+            Ok(Some(launched)) => launched,
+            result => {
+                self.inner.write().await.launching.remove(&reservation);
+                return result.map(|_| None);
+            }
+        };
+
+        let grace_secs = project
+            .stop_grace_period_secs
+            .unwrap_or(settings.stop_grace_period_secs);
+        let instance = ProcessInstance {
+            id: instance_id,
+            project_id: project.id,
+            workspace_id: handle.meta.id,
+            name,
+            config_id: launch_config.id,
+            config: launch_config.config,
+            session_id: launched.session_id,
+            pid: launched.child.id(),
+            started_at: Utc::now(),
+        };
+
+        let mut child = launched.child;
+        let mut inner = self.inner.write().await;
+        inner.launching.remove(&reservation);
+        let buffer = inner.reset_output_buffer(&instance);
+        process::capture_output(app, &instance, &mut child, &buffer);
+        inner.running_processes.insert(
+            instance_id,
+            RunningProcess {
+                child,
+                instance: instance.clone(),
+                grace_period: Duration::from_secs(grace_secs),
+                restart_policy: project.restart_policy.clone(),
+                script: launched.script,
+            },
+        );
+        Ok(Some(instance))
+    }
+
+    /// Stops the given instances concurrently, each with its own grace period.
+    /// Instances that fail to stop stay tracked so the stop can be retried.
+    pub async fn stop_processes(&self, instance_ids: &[Uuid]) -> AppResult<Vec<ProcessStopResult>> {
+        // Release the lock before waiting out the grace periods. The names
+        // stay reserved until the process groups are gone.
+        let entries: Vec<RunningProcess> = {
+            let mut inner = self.inner.write().await;
+            let entries: Vec<RunningProcess> = instance_ids
+                .iter()
+                .filter_map(|id| {
+                    inner.supervisor.cancel(*id);
+                    inner.running_processes.remove(id)
+                })
+                .collect();
+            for entry in &entries {
+                let instance = &entry.instance;
+                inner
+                    .stopping
+                    .insert((instance.project_id, instance.name.clone()));
+            }
+            entries
+        };
+
+        let mut tasks = JoinSet::new();
+        let mut instances = HashMap::new();
+        for mut entry in entries {
+            let instance = entry.instance.clone();
+            let task = tasks.spawn(async move {
+                let result = project::stop_project(&mut entry.child, entry.grace_period).await;
+                (entry, result)
+            });
+            instances.insert(task.id(), instance);
+        }
+
+        let mut results = Vec::new();
+        let mut first_error = None;
+        while let Some(joined) = tasks.join_next_with_id().await {
+            let task_id = match &joined {
+                Ok((id, _)) => *id,
+                Err(err) => err.id(),
+            };
+            let instance = &instances[&task_id];
+            let reservation = (instance.project_id, instance.name.clone());
+
+            let (mut entry, result) = match joined {
+                Ok((_, stopped)) => stopped,
+                Err(err) => {
+                    // The entry went down with the task, so it cannot be kept
+                    // for a retry
+                    self.inner.write().await.stopping.remove(&reservation);
+                    log::error!("stopping process '{}' failed: {err}", instance.name);
+                    results.push(ProcessStopResult {
+                        instance_id: instance.id,
+                        project_id: instance.project_id,
+                        name: instance.name.clone(),
+                        outcome: StopOutcome::Failed,
+                    });
+                    continue;
+                }
+            };
+            let outcome = {
+                let mut inner = self.inner.write().await;
+                inner.stopping.remove(&reservation);
+                match result {
+                    Ok(outcome) => outcome,
+                    Err(err) => {
+                        inner.running_processes.insert(entry.instance.id, entry);
+                        first_error.get_or_insert(err);
+                        continue;
+                    }
+                }
+            };
+
+            let stopped = outcome != StopOutcome::AlreadyExited;
+            if let Err(err) = self.finish_session(&mut entry, stopped).await {
+                let session_id = entry.instance.session_id;
+                log::warn!("failed to close session {session_id}: {err}");
+            }
+            results.push(ProcessStopResult {
+                instance_id: entry.instance.id,
+                project_id: entry.instance.project_id,
+                name: entry.instance.name,
+                outcome,
+            });
+        }
+
+        match first_error {
+            Some(err) => Err(err),
+            None => Ok(results),
+        }
+    }
+
+    pub async fn add_watched_folder(
+        &self,
+        workspace_id: Uuid,
+        payload: WatchedFolderInput,
+    ) -> AppResult<(WorkspaceRecord, WatchedFolder)> {
+        let path = project::sanitize_path_buf(payload.path);
+        if !path.is_dir() {
+            return Err(AppError::Validation(format!(
+                "watched folder is not a directory: {}",
+                path.display()
+            )));
+        }
+        scan::compile_globs(&payload.ignore_globs)?;
+        let max_depth = payload.max_depth.unwrap_or_else(default_watch_depth);
+        if max_depth == 0 || max_depth > MAX_WATCH_DEPTH {
+            return Err(AppError::Validation(format!(
+                "watch depth must be between 1 and {MAX_WATCH_DEPTH}"
+            )));
+        }
+
+        let (record, folder) = {
+            let mut inner = self.inner.write().await;
+            let workspace = inner
                 .config
-                .active_workspace_id
-                .ok_or_else(|| AppError::Validation("no active workspace selected".into()))?,
+                .workspaces
+                .iter_mut()
+                .find(|ws| ws.id == workspace_id)
+                .ok_or_else(|| AppError::WorkspaceNotFound(workspace_id.to_string()))?;
+            if workspace
+                .watched_folders
+                .iter()
+                .any(|folder| folder.path == path)
+            {
+                return Err(AppError::Validation(format!(
+                    "folder is already watched: {}",
+                    path.display()
+                )));
+            }
+
+            let folder = WatchedFolder {
+                id: Uuid::new_v4(),
+                path,
+                mode: payload.mode,
+                max_depth,
+                ignore_globs: payload.ignore_globs,
+            };
+            workspace.watched_folders.push(folder.clone());
+            workspace.updated_at = Utc::now();
+            let record = workspace.clone();
+            inner.persist_config()?;
+            (record, folder)
         };
 
-        let meta = inner
-            .config
-            .find_workspace(id)
-            .ok_or_else(|| AppError::WorkspaceNotFound(id.to_string()))?
-            .clone();
-        let pool = inner
-            .workspace_pools
-            .get(&id)
+        self.sync_watches().await;
+        Ok((record, folder))
+    }
+
+    pub async fn remove_watched_folder(
+        &self,
+        workspace_id: Uuid,
+        watch_id: Uuid,
+    ) -> AppResult<WorkspaceRecord> {
+        let record = {
+            let mut inner = self.inner.write().await;
+            let workspace = inner
+                .config
+                .workspaces
+                .iter_mut()
+                .find(|ws| ws.id == workspace_id)
+                .ok_or_else(|| AppError::WorkspaceNotFound(workspace_id.to_string()))?;
+            let before = workspace.watched_folders.len();
+            workspace
+                .watched_folders
+                .retain(|folder| folder.id != watch_id);
+            if workspace.watched_folders.len() == before {
+                return Err(AppError::Validation(format!(
+                    "watched folder not found: {watch_id}"
+                )));
+            }
+            workspace.updated_at = Utc::now();
+            let record = workspace.clone();
+            inner.persist_config()?;
+            record
+        };
+
+        self.sync_watches().await;
+        let forgotten = match self.workspace_handle(Some(workspace_id)).await {
+            Ok(handle) => watch::forget_folder(&handle, watch_id).await,
+            Err(err) => Err(err),
+        };
+        // The folder is no longer watched either way, e.g. when the workspace
+        // database is unavailable
+        if let Err(err) = forgotten {
+            log::warn!("failed to drop the suggestions of watched folder {watch_id}: {err}");
+        }
+        Ok(record)
+    }
+
+    /// Fills in the git status of each project, from the cache unless
+    /// `refresh` is set or the cached entry has expired.
+    pub async fn attach_git_status(&self, projects: &mut [Project], refresh: bool) {
+        let mut missing = Vec::new();
+        {
+            let inner = self.inner.read().await;
+            for project in projects.iter_mut() {
+                project.git = if refresh {
+                    None
+                } else {
+                    inner.git_statuses.get(&project.path)
+                };
+                if project.git.is_none() {
+                    missing.push(project.path.clone());
+                }
+            }
+        }
+        if missing.is_empty() {
+            return;
+        }
+
+        let mut fetched = git::query_all(missing).await;
+        let mut inner = self.inner.write().await;
+        for project in projects.iter_mut().filter(|project| project.git.is_none()) {
+            if let Some(status) = fetched.remove(&project.path) {
+                inner
+                    .git_statuses
+                    .insert(project.path.clone(), status.clone());
+                project.git = Some(status);
+            }
+        }
+    }
+
+    /// Watches the folders of every workspace for new projects, after
+    /// catching up on those created while the app was closed.
+    pub async fn run_folder_watcher(self, app: AppHandle) {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            // The receiver only goes away when the app shuts down
+            let _ = sender.send(event);
+        });
+        match watcher {
+            Ok(watcher) => self.inner.write().await.folder_watcher = Some(watcher),
+            Err(err) => log::warn!("failed to start folder watcher: {err}"),
+        }
+        self.sync_watches().await;
+        self.reconcile_watched_folders(&app).await;
+
+        while let Some(event) = receiver.recv().await {
+            let mut paths = HashSet::new();
+            collect_created_paths(event, &mut paths);
+            // Let bursts such as a `git clone` settle before looking, but
+            // not forever while events keep coming
+            let deadline = Instant::now() + MAX_WATCH_DEBOUNCE;
+            loop {
+                let wait = WATCH_DEBOUNCE.min(deadline.saturating_duration_since(Instant::now()));
+                match tokio::time::timeout(wait, receiver.recv()).await {
+                    Ok(Some(event)) => collect_created_paths(event, &mut paths),
+                    _ => break,
+                }
+            }
+            if paths.is_empty() {
+                continue;
+            }
+            self.process_created_paths(&app, paths).await;
+            // New directories need watches, new projects no longer do
+            self.sync_watches().await;
+        }
+    }
+
+    /// Scans every watched folder in full. Used at startup to pick up what
+    /// changed while the app was not running.
+    pub async fn reconcile_watched_folders(&self, app: &AppHandle) {
+        for workspace in self.list_workspaces().await {
+            for folder in &workspace.watched_folders {
+                self.reconcile_watched_folder(app, workspace.id, folder)
+                    .await;
+            }
+        }
+    }
+
+    pub async fn reconcile_watched_folder(
+        &self,
+        app: &AppHandle,
+        workspace_id: Uuid,
+        folder: &WatchedFolder,
+    ) {
+        let ignore = match scan::compile_globs(&folder.ignore_globs) {
+            Ok(ignore) => ignore,
+            Err(err) => {
+                log::warn!("skipping watched folder {}: {err}", folder.path.display());
+                return;
+            }
+        };
+        let root = folder.path.clone();
+        let max_depth = folder.max_depth;
+        let candidates =
+            tokio::task::spawn_blocking(move || scan::find_projects(&root, max_depth, &ignore))
+                .await;
+        match candidates {
+            Ok(candidates) => {
+                self.apply_watch_candidates(app, workspace_id, folder, candidates)
+                    .await
+            }
+            Err(err) => log::warn!("failed to scan {}: {err}", folder.path.display()),
+        }
+    }
+
+    async fn process_created_paths(&self, app: &AppHandle, paths: HashSet<PathBuf>) {
+        for workspace in self.list_workspaces().await {
+            for folder in &workspace.watched_folders {
+                let Ok(ignore) = scan::compile_globs(&folder.ignore_globs) else {
+                    continue;
+                };
+                let roots: BTreeSet<PathBuf> = paths
+                    .iter()
+                    .filter_map(|path| {
+                        scan::enclosing_project(&folder.path, path, folder.max_depth, &ignore)
+                    })
+                    .collect();
+                let candidates = roots
+                    .iter()
+                    .filter_map(|root| scan::candidate_at(root))
+                    .collect::<Vec<_>>();
+                if !candidates.is_empty() {
+                    self.apply_watch_candidates(app, workspace.id, folder, candidates)
+                        .await;
+                }
+            }
+        }
+    }
+
+    async fn apply_watch_candidates(
+        &self,
+        app: &AppHandle,
+        workspace_id: Uuid,
+        folder: &WatchedFolder,
+        candidates: Vec<ScanCandidate>,
+    ) {
+        let result = async {
+            let handle = self.workspace_handle(Some(workspace_id)).await?;
+            watch::apply_candidates(&handle, folder, candidates).await
+        }
+        .await;
+        match result {
+            Ok(event) if event.added.is_empty() && event.suggested.is_empty() => {}
+            Ok(event) => {
+                if let Err(err) = app.emit(watch::WATCH_EVENT, event) {
+                    log::warn!("failed to emit watched folder event: {err}");
+                }
+            }
+            Err(err) => log::warn!(
+                "failed to register projects from {}: {err}",
+                folder.path.display()
+            ),
+        }
+    }
+
+    /// Brings the watcher in line with the watched folders of all workspaces.
+    /// Each directory from [`scan::watch_dirs`] gets a watch of its own, as
+    /// recursive watches would cover whole dependency and build trees.
+    async fn sync_watches(&self) {
+        let folders: Vec<WatchedFolder> = {
+            let inner = self.inner.read().await;
+            inner
+                .config
+                .workspaces
+                .iter()
+                .flat_map(|ws| ws.watched_folders.iter().cloned())
+                .collect()
+        };
+        let wanted = tokio::task::spawn_blocking(move || {
+            folders
+                .iter()
+                .flat_map(|folder| {
+                    let ignore = scan::compile_globs(&folder.ignore_globs).unwrap_or_default();
+                    scan::watch_dirs(&folder.path, folder.max_depth, &ignore)
+                })
+                .collect::<HashSet<PathBuf>>()
+        })
+        .await;
+        let wanted = match wanted {
+            Ok(wanted) => wanted,
+            Err(err) => {
+                log::warn!("failed to list watched directories: {err}");
+                return;
+            }
+        };
+
+        let mut inner = self.inner.write().await;
+        let AppStateInner {
+            folder_watcher,
+            watched_paths,
+            ..
+        } = &mut *inner;
+        let Some(watcher) = folder_watcher.as_mut() else {
+            return;
+        };
+
+        for path in watched_paths.difference(&wanted) {
+            // Watches on deleted directories are already gone
+            if let Err(err) = watcher.unwatch(path) {
+                log::debug!("failed to stop watching {}: {err}", path.display());
+            }
+        }
+        watched_paths.retain(|path| wanted.contains(path));
+        for path in wanted {
+            if watched_paths.contains(&path) {
+                continue;
+            }
+            match watcher.watch(&path, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    watched_paths.insert(path);
+                }
+                Err(err) => log::warn!("failed to watch {}: {err}", path.display()),
+            }
+        }
+    }
+
+    /// Checks the path of every project now and refreshes the git remotes
+    /// that relocation matches on.
+    pub async fn check_project_paths(
+        &self,
+        handle: &WorkspaceHandle,
+    ) -> AppResult<Vec<ProjectPathHealth>> {
+        let statuses = health::check_projects(handle).await?;
+        health::record_remotes(handle, &statuses).await?;
+        self.remember_path_health(&statuses).await;
+        Ok(statuses)
+    }
+
+    /// Records that a project's path is available again, e.g. after it was
+    /// relocated, without re-checking the rest of the workspace.
+    pub async fn mark_path_available(&self, project_id: Uuid) {
+        self.inner
+            .write()
+            .await
+            .path_health
+            .insert(project_id, PathHealth::Available);
+    }
+
+    /// Re-checks project paths periodically and emits the ones whose health
+    /// changed, e.g. because a drive was unplugged. Only workspaces with an
+    /// open pool are checked: reopening idle ones every interval would keep
+    /// them from ever being evicted. A workspace is monitored again from the
+    /// first tick after something opens it.
+    pub async fn run_path_monitor(self, app: AppHandle) {
+        let mut interval = tokio::time::interval(PATH_HEALTH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let handles = self.inner.read().await.workspace_handles();
+            for handle in handles {
+                let statuses = match health::check_projects(&handle).await {
+                    Ok(statuses) => statuses,
+                    Err(err) => {
+                        log::warn!("failed to check paths of {}: {err}", handle.meta.name);
+                        continue;
+                    }
+                };
+                let changed = self.remember_path_health(&statuses).await;
+                if changed.is_empty() {
+                    continue;
+                }
+                let event = PathHealthEvent {
+                    workspace_id: handle.meta.id,
+                    projects: changed,
+                };
+                if let Err(err) = app.emit(health::PATH_HEALTH_EVENT, event) {
+                    log::warn!("failed to emit path health event: {err}");
+                }
+            }
+        }
+    }
+
+    /// Stores the latest health of each project and returns the entries that
+    /// differ from the previous check. Projects seen for the first time only
+    /// count when their path is not available.
+    async fn remember_path_health(&self, statuses: &[ProjectPathHealth]) -> Vec<ProjectPathHealth> {
+        let mut inner = self.inner.write().await;
+        statuses
+            .iter()
+            .filter(|status| {
+                let previous = inner.path_health.insert(status.project_id, status.health);
+                previous.unwrap_or(PathHealth::Available) != status.health
+            })
             .cloned()
-            .ok_or_else(|| AppError::WorkspaceNotFound(id.to_string()))?;
+            .collect()
+    }
 
-        Ok(WorkspaceHandle { meta, pool })
+    /// Reaps exited processes and performs scheduled restarts until the app
+    /// shuts down, so crashes are noticed even when nobody polls.
+    pub async fn run_supervisor(self, app: AppHandle) {
+        let mut interval = tokio::time::interval(SUPERVISOR_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.supervise(&app).await;
+        }
+    }
+
+    async fn supervise(&self, app: &AppHandle) {
+        self.reap_processes().await;
+
+        let (notices, due) = {
+            let mut inner = self.inner.write().await;
+            let notices = std::mem::take(&mut inner.supervisor.notices);
+            let due = inner.supervisor.take_due(Instant::now());
+            (notices, due)
+        };
+
+        for notice in notices {
+            let emitted = match notice {
+                SupervisorNotice::Restarting(event) => app.emit(RESTART_EVENT, event),
+                SupervisorNotice::CrashLoop(event) => app.emit(CRASH_LOOP_EVENT, event),
+            };
+            if let Err(err) = emitted {
+                log::warn!("failed to emit supervisor event: {err}");
+            }
+        }
+
+        for restart in due {
+            let result = async {
+                let handle = self.workspace_handle(Some(restart.workspace_id)).await?;
+                let project = project::get_project(&handle, restart.project_id).await?;
+                let request = LaunchRequest {
+                    config_id: Some(restart.config_id),
+                    name: Some(restart.name),
+                    instance_id: Some(restart.instance_id),
+                };
+                self.launch_tracked(app, &handle, &project, request).await
+            }
+            .await;
+            if let Err(err) = result {
+                log::warn!("failed to restart process {}: {err}", restart.instance_id);
+                let mut inner = self.inner.write().await;
+                inner.supervisor.on_restart_failed(restart.instance_id);
+            }
+        }
+    }
+
+    /// Closes the session of a process taken out of `running_processes`.
+    /// Processes that had already exited before the stop are not recorded
+    /// as stopped by the user.
+    pub async fn finish_session(&self, entry: &mut RunningProcess, stopped: bool) -> AppResult<()> {
+        let exit_status = entry.child.try_wait().ok().flatten();
+        let status = if stopped {
+            SessionStatus::Stopped
+        } else {
+            session::status_from_exit(exit_status.as_ref())
+        };
+        let instance = &entry.instance;
+        let handle = self.workspace_handle(Some(instance.workspace_id)).await?;
+        session::close_session(&handle, instance.session_id, status, exit_status).await
     }
 
     pub async fn get_settings(&self) -> AppSettings {
@@ -319,9 +1414,28 @@ impl AppState {
         inner.config.settings.clone()
     }
 
-    pub async fn update_settings(&self, payload: AppSettingsUpdate) -> AppResult<AppSettings> {
-        let mut inner = self.inner.write().await;
+    /// Saves a project after checking that the presets it references exist.
+    pub async fn upsert_project(
+        &self,
+        handle: &WorkspaceHandle,
+        payload: ProjectInput,
+    ) -> AppResult<Project> {
+        let _presets = self.presets.lock().await;
+        let settings = self.get_settings().await;
+        let configs = payload.open_config.iter().chain(
+            payload
+                .launch_configs
+                .iter()
+                .flatten()
+                .map(|input| &input.config),
+        );
+        presets::check_references(configs, &settings.launch_presets)?;
+        project::upsert_project(handle, payload).await
+    }
 
+    pub async fn update_settings(&self, payload: AppSettingsUpdate) -> AppResult<AppSettings> {
+        // Held until the settings are written, see `presets`
+        let _presets = self.presets.lock().await;
         let font_family = payload
             .font_family
             .map(|value| value.trim().to_string())
@@ -340,10 +1454,15 @@ impl AppState {
             }
 
             // Sanitize executable path
-            if let crate::models::OpenConfig::CustomApp { executable, .. } = &mut preset.config {
+            if let OpenConfig::CustomApp { executable, .. } = &mut preset.config {
                 *executable = project::sanitize_path_buf(executable.clone());
             }
 
+            if matches!(preset.config, OpenConfig::Preset { .. }) {
+                return Err(AppError::Validation(format!(
+                    "launch preset '{name}' cannot reference another preset"
+                )));
+            }
             project::validate_open_config(&preset.config)?;
 
             let id = preset.id.unwrap_or_else(Uuid::new_v4);
@@ -364,6 +1483,61 @@ impl AppState {
             });
         }
 
+        let current = self.get_settings().await;
+        let stop_grace_period_secs = payload
+            .stop_grace_period_secs
+            .unwrap_or(current.stop_grace_period_secs)
+            .min(project::MAX_STOP_GRACE_PERIOD_SECS);
+
+        // Workspaces are updated without locking the state, and the presets
+        // are only dropped once no workspace needs them any more
+        let removed: Vec<LaunchPreset> = current
+            .launch_presets
+            .into_iter()
+            .filter(|old| !launch_presets.iter().any(|preset| preset.id == old.id))
+            .collect();
+        if !removed.is_empty() {
+            // Every workspace is read before any is changed, so one that cannot
+            // be opened fails the update up front
+            let references = self.collect_preset_references().await?;
+            if payload.inline_removed_presets {
+                let mut workspace_ids: Vec<Uuid> = references
+                    .iter()
+                    .filter(|(id, _)| removed.iter().any(|preset| preset.id == *id))
+                    .map(|(_, reference)| reference.workspace_id)
+                    .collect();
+                workspace_ids.dedup();
+                // Should one still fail, the workspaces done so far hold copies
+                // of presets that are kept, and retrying finishes the rest
+                for id in workspace_ids {
+                    let handle = self.workspace_handle(Some(id)).await?;
+                    presets::inline_presets(&handle, &removed).await?;
+                }
+            } else {
+                let in_use: Vec<String> = removed
+                    .iter()
+                    .filter_map(|preset| {
+                        let users: Vec<String> = references
+                            .iter()
+                            .filter(|(id, _)| *id == preset.id)
+                            .map(|(_, reference)| {
+                                format!("{} / {}", reference.workspace_name, reference.project_name)
+                            })
+                            .collect();
+                        (!users.is_empty())
+                            .then(|| format!("'{}' ({})", preset.name, users.join(", ")))
+                    })
+                    .collect();
+                if !in_use.is_empty() {
+                    return Err(AppError::Validation(format!(
+                        "launch presets still in use: {}",
+                        in_use.join("; ")
+                    )));
+                }
+            }
+        }
+
+        let mut inner = self.inner.write().await;
         inner.config.settings = AppSettings {
             theme: payload.theme,
             accent_color: payload.accent_color,
@@ -371,21 +1545,66 @@ impl AppState {
             font_family,
             font_size,
             launch_presets,
+            stop_grace_period_secs,
+            trash_retention_days: payload
+                .trash_retention_days
+                .unwrap_or(current.trash_retention_days)
+                .min(project::MAX_TRASH_RETENTION_DAYS),
         };
         inner.persist_config()?;
         Ok(inner.config.settings.clone())
     }
 
+    /// Lists the launch configurations referencing each preset, across all
+    /// workspaces.
+    pub async fn preset_usage(&self) -> AppResult<Vec<PresetUsage>> {
+        let launch_presets = {
+            let inner = self.inner.read().await;
+            inner.config.settings.launch_presets.clone()
+        };
+        let mut references = self.collect_preset_references().await?;
+
+        Ok(launch_presets
+            .into_iter()
+            .map(|preset| {
+                let (used, rest) = references.drain(..).partition(|(id, _)| *id == preset.id);
+                references = rest;
+                PresetUsage {
+                    preset_id: preset.id,
+                    preset_name: preset.name,
+                    references: used.into_iter().map(|(_, reference)| reference).collect(),
+                }
+            })
+            .collect())
+    }
+
     pub async fn persist_config(&self) -> AppResult<()> {
         let inner = self.inner.read().await;
         inner.persist_config()
     }
 }
 
+/// Keeps the paths of entries that were created or moved in.
+fn collect_created_paths(event: notify::Result<Event>, paths: &mut HashSet<PathBuf>) {
+    match event {
+        Ok(event) => {
+            if matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))
+            ) {
+                paths.extend(event.paths);
+            }
+        }
+        Err(err) => log::warn!("folder watcher error: {err}"),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct WorkspaceHandle {
     pub meta: WorkspaceRecord,
     pub pool: SqlitePool,
+    /// Keeps the pool from being evicted while the handle exists.
+    _lease: Arc<()>,
 }
 
 impl AppConfig {
@@ -406,6 +1625,7 @@ impl AppConfig {
             database_path,
             created_at: now,
             updated_at: now,
+            watched_folders: Vec::new(),
         };
 
         Ok(Self {
@@ -432,6 +1652,16 @@ impl AppConfig {
             changed = true;
         }
 
+        if self.settings.stop_grace_period_secs > project::MAX_STOP_GRACE_PERIOD_SECS {
+            self.settings.stop_grace_period_secs = project::MAX_STOP_GRACE_PERIOD_SECS;
+            changed = true;
+        }
+
+        if self.settings.trash_retention_days > project::MAX_TRASH_RETENTION_DAYS {
+            self.settings.trash_retention_days = project::MAX_TRASH_RETENTION_DAYS;
+            changed = true;
+        }
+
         if let Some(family) = &self.settings.font_family {
             let trimmed = family.trim();
             if trimmed.is_empty() {
@@ -469,7 +1699,294 @@ impl AppConfig {
     }
 }
 
+impl Supervisor {
+    /// Records a process that exited on its own and schedules its restart if
+    /// the policy asks for one, or gives up once the crash-loop limit is hit.
+    fn on_exit(&mut self, entry: &RunningProcess, status: Option<&ExitStatus>) {
+        let instance = &entry.instance;
+        let failed = !status.is_some_and(|status| status.success());
+        let wanted = match entry.restart_policy.mode {
+            RestartMode::Never => false,
+            RestartMode::OnFailure => failed,
+            RestartMode::Always => true,
+        };
+        if !wanted {
+            self.restarts.remove(&instance.id);
+            return;
+        }
+
+        let tracker = self
+            .restarts
+            .entry(instance.id)
+            .or_insert_with(|| RestartTracker {
+                project_id: instance.project_id,
+                workspace_id: instance.workspace_id,
+                config_id: instance.config_id,
+                name: instance.name.clone(),
+                policy: entry.restart_policy.clone(),
+                history: VecDeque::new(),
+                due: None,
+            });
+        tracker.policy = entry.restart_policy.clone();
+        self.schedule(instance.id, status.and_then(ExitStatus::code));
+    }
+
+    /// Counts a restart that could not be launched as another failure.
+    fn on_restart_failed(&mut self, instance_id: Uuid) {
+        if self.restarts.contains_key(&instance_id) {
+            self.schedule(instance_id, None);
+        }
+    }
+
+    pub fn cancel(&mut self, instance_id: Uuid) {
+        self.restarts.remove(&instance_id);
+    }
+
+    /// Cancels pending restarts of every instance of a project.
+    pub fn cancel_project(&mut self, project_id: Uuid) {
+        self.restarts
+            .retain(|_, tracker| tracker.project_id != project_id);
+    }
+
+    fn schedule(&mut self, instance_id: Uuid, exit_code: Option<i32>) {
+        let Some(tracker) = self.restarts.get_mut(&instance_id) else {
+            return;
+        };
+        let now = Instant::now();
+        let window = Duration::from_secs(tracker.policy.window_secs);
+        while tracker
+            .history
+            .front()
+            .is_some_and(|at| now.duration_since(*at) > window)
+        {
+            tracker.history.pop_front();
+        }
+
+        let policy = tracker.policy.clone();
+        let project_id = tracker.project_id;
+        let name = tracker.name.clone();
+        if tracker.history.len() >= policy.max_retries as usize {
+            self.restarts.remove(&instance_id);
+            self.notices
+                .push(SupervisorNotice::CrashLoop(CrashLoopEvent {
+                    instance_id,
+                    project_id,
+                    name,
+                    restarts: policy.max_retries,
+                    window_secs: policy.window_secs,
+                    exit_code,
+                }));
+            return;
+        }
+
+        let attempt = tracker.history.len() as u32 + 1;
+        let delay_ms = restart_backoff_ms(&policy, attempt);
+        tracker.history.push_back(now);
+        tracker.due = Some(now + Duration::from_millis(delay_ms));
+        self.notices
+            .push(SupervisorNotice::Restarting(ProcessRestartEvent {
+                instance_id,
+                project_id,
+                name,
+                attempt,
+                delay_ms,
+                exit_code,
+            }));
+    }
+
+    fn take_due(&mut self, now: Instant) -> Vec<DueRestart> {
+        self.restarts
+            .iter_mut()
+            .filter(|(_, tracker)| tracker.due.is_some_and(|due| due <= now))
+            .map(|(instance_id, tracker)| {
+                tracker.due = None;
+                DueRestart {
+                    instance_id: *instance_id,
+                    project_id: tracker.project_id,
+                    workspace_id: tracker.workspace_id,
+                    config_id: tracker.config_id,
+                    name: tracker.name.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Doubles the delay with every attempt, capped at `max_backoff_ms`.
+fn restart_backoff_ms(policy: &RestartPolicy, attempt: u32) -> u64 {
+    let factor = 1u64
+        .checked_shl(attempt.saturating_sub(1))
+        .unwrap_or(u64::MAX);
+    policy
+        .initial_backoff_ms
+        .saturating_mul(factor)
+        .min(policy.max_backoff_ms)
+}
+
+/// Opens a workspace database and brings it up to date: migrations,
+/// sessions left open by a crash on `first_open` and expired trash. Unless
+/// `create` is set, a missing database is an error rather than silently
+/// replaced by an empty one.
+async fn open_workspace_pool(
+    database_path: &Path,
+    create: bool,
+    first_open: bool,
+    trash_retention_days: u32,
+) -> AppResult<SqlitePool> {
+    if !create && !database_path.is_file() {
+        return Err(AppError::Validation(format!(
+            "workspace database is missing: {}",
+            database_path.display()
+        )));
+    }
+    if let Some(parent) = database_path.parent() {
+        if !parent.exists() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+
+    let options = apply_default_pragmas(SqliteConnectOptions::new().filename(database_path));
+    let pool = SqlitePoolOptions::new()
+        .max_connections(POOL_MAX_CONNECTIONS)
+        .connect_with(options)
+        .await?;
+    let prepared = async {
+        init_workspace_schema(&pool, database_path).await?;
+        if first_open {
+            session::close_abandoned_sessions(&pool).await?;
+        }
+        project::purge_expired(&pool, trash_retention_days).await?;
+        Ok::<_, AppError>(())
+    }
+    .await;
+    if let Err(err) = prepared {
+        pool.close().await;
+        return Err(err);
+    }
+    Ok(pool)
+}
+
+/// An open workspace pool. The lease is shared with every handle given out,
+/// so that a pool still held by a command or task is never evicted.
+#[derive(Debug)]
+struct OpenPool {
+    pool: SqlitePool,
+    lease: Arc<()>,
+    last_used: std::sync::Mutex<Instant>,
+}
+
+impl OpenPool {
+    fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            lease: Arc::new(()),
+            last_used: std::sync::Mutex::new(Instant::now()),
+        }
+    }
+
+    fn handle(&self, meta: WorkspaceRecord) -> WorkspaceHandle {
+        WorkspaceHandle {
+            meta,
+            pool: self.pool.clone(),
+            _lease: self.lease.clone(),
+        }
+    }
+
+    fn touch(&self) {
+        let mut last_used = self
+            .last_used
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        *last_used = Instant::now();
+    }
+
+    fn idle_for(&self) -> Duration {
+        self.last_used
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .elapsed()
+    }
+
+    fn is_leased(&self) -> bool {
+        Arc::strong_count(&self.lease) > 1
+    }
+}
+
 impl AppStateInner {
+    /// Handles of every workspace with an open database.
+    fn workspace_handles(&self) -> Vec<WorkspaceHandle> {
+        self.config
+            .workspaces
+            .iter()
+            .filter_map(|meta| {
+                let open = self.workspace_pools.get(&meta.id)?;
+                Some(open.handle(meta.clone()))
+            })
+            .collect()
+    }
+
+    /// The workspace with `workspace_id`, or the active one.
+    fn find_workspace(&self, workspace_id: Option<Uuid>) -> AppResult<&WorkspaceRecord> {
+        let id = match workspace_id {
+            Some(id) => id,
+            None => self
+                .config
+                .active_workspace_id
+                .ok_or_else(|| AppError::Validation("no active workspace selected".into()))?,
+        };
+        self.config
+            .find_workspace(id)
+            .ok_or_else(|| AppError::WorkspaceNotFound(id.to_string()))
+    }
+
+    /// Keeps a newly opened pool and hands out its first handle, then closes
+    /// other pools if the connection budget is exceeded.
+    fn insert_pool(&mut self, meta: WorkspaceRecord, pool: SqlitePool) -> WorkspaceHandle {
+        let id = meta.id;
+        let open = OpenPool::new(pool);
+        let handle = open.handle(meta);
+        self.workspace_pools.insert(id, open);
+        self.workspace_errors.remove(&id);
+        self.opened_workspaces.insert(id);
+        self.evict_pools(None);
+        handle
+    }
+
+    /// Closes pools that nobody holds, other than the active workspace's:
+    /// those idle for at least `idle_timeout`, and the least recently used
+    /// ones while the open pools could exceed [`MAX_OPEN_CONNECTIONS`]. Pools
+    /// in use may keep the total above it until a later eviction. Pools are
+    /// closed in the background, so the state is not locked while they drain.
+    fn evict_pools(&mut self, idle_timeout: Option<Duration>) {
+        self.unavailable_leases
+            .retain(|lease| lease.strong_count() > 0);
+        let unavailable = self.unavailable_leases.len() as u32;
+        let active = self.config.active_workspace_id;
+        let mut candidates: Vec<(Uuid, Duration)> = self
+            .workspace_pools
+            .iter()
+            .filter(|(id, open)| Some(**id) != active && !open.is_leased())
+            .map(|(id, open)| (*id, open.idle_for()))
+            .collect();
+        candidates.sort_by_key(|(_, idle)| std::cmp::Reverse(*idle));
+
+        for (id, idle) in candidates {
+            let connections =
+                self.workspace_pools.len() as u32 * POOL_MAX_CONNECTIONS + unavailable;
+            let over_budget = connections > MAX_OPEN_CONNECTIONS;
+            let expired = idle_timeout.is_some_and(|timeout| idle >= timeout);
+            if !over_budget && !expired {
+                continue;
+            }
+            if let Some(open) = self.workspace_pools.remove(&id) {
+                tauri::async_runtime::spawn(async move {
+                    open.pool.close().await;
+                    log::debug!("closed the database pool of workspace {id}");
+                });
+            }
+        }
+    }
+
     fn persist_config(&self) -> AppResult<()> {
         if let Some(parent) = self.config_path.parent() {
             if !parent.exists() {
@@ -481,6 +1998,25 @@ impl AppStateInner {
         Ok(())
     }
 
+    /// Returns the output buffer for an instance, emptied for a fresh launch.
+    /// Output of earlier instances with the same name is dropped, since the
+    /// new instance replaces them.
+    pub fn reset_output_buffer(&mut self, instance: &ProcessInstance) -> SharedOutput {
+        let running = &self.running_processes;
+        self.process_output.retain(|id, buffer| {
+            *id == instance.id
+                || running.contains_key(id)
+                || !process::lock_output(buffer).belongs_to(instance.project_id, &instance.name)
+        });
+        let buffer = self
+            .process_output
+            .entry(instance.id)
+            .or_insert_with(|| process::OutputBuffer::shared(instance))
+            .clone();
+        process::lock_output(&buffer).clear();
+        buffer
+    }
+
     fn default_workspace_db_path(&self, id: &Uuid) -> PathBuf {
         self.base_dir
             .join("workspaces")
@@ -488,3 +2024,23 @@ impl AppStateInner {
             .join("projects.sqlite")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restart_backoff_doubles_up_to_the_cap() {
+        let policy = RestartPolicy {
+            initial_backoff_ms: 500,
+            max_backoff_ms: 5_000,
+            ..RestartPolicy::default()
+        };
+        assert_eq!(restart_backoff_ms(&policy, 0), 500);
+        assert_eq!(restart_backoff_ms(&policy, 1), 500);
+        assert_eq!(restart_backoff_ms(&policy, 2), 1_000);
+        assert_eq!(restart_backoff_ms(&policy, 4), 4_000);
+        assert_eq!(restart_backoff_ms(&policy, 5), 5_000);
+        assert_eq!(restart_backoff_ms(&policy, 200), 5_000);
+    }
+}
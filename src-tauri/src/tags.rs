@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use sqlx::{SqliteConnection, SqlitePool};
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, AppResult},
+    models::{Project, Tag, TagInput, TagRow, TagSummary},
+    project, search,
+    state::WorkspaceHandle,
+};
+
+/// Loads the tags of one project, or of every project when `project_id` is
+/// `None`, grouped by project id and sorted by name.
+pub async fn load_tags(
+    pool: &SqlitePool,
+    project_id: Option<&str>,
+) -> AppResult<HashMap<String, Vec<Tag>>> {
+    let rows: Vec<(String, String, String, Option<String>)> = sqlx::query_as(
+        r#"
+        SELECT pt.project_id, t.id, t.name, t.color
+        FROM project_tags pt
+        JOIN tags t ON t.id = pt.tag_id
+        WHERE (?1 IS NULL OR pt.project_id = ?1)
+        ORDER BY t.name COLLATE NOCASE
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut grouped: HashMap<String, Vec<Tag>> = HashMap::new();
+    for (project_id, id, name, color) in rows {
+        let tag = row_to_tag(TagRow { id, name, color })?;
+        grouped.entry(project_id).or_default().push(tag);
+    }
+    Ok(grouped)
+}
+
+pub async fn list_tags(handle: &WorkspaceHandle) -> AppResult<Vec<TagSummary>> {
+    let rows: Vec<(String, String, Option<String>, i64)> = sqlx::query_as(
+        r#"
+        SELECT t.id, t.name, t.color, COUNT(p.id)
+        FROM tags t
+        LEFT JOIN project_tags pt ON pt.tag_id = t.id
+        LEFT JOIN projects p ON p.id = pt.project_id AND p.deleted_at IS NULL
+        GROUP BY t.id
+        ORDER BY t.name COLLATE NOCASE
+        "#,
+    )
+    .fetch_all(&handle.pool)
+    .await?;
+    rows.into_iter()
+        .map(|(id, name, color, project_count)| {
+            let tag = row_to_tag(TagRow { id, name, color })?;
+            Ok(TagSummary {
+                id: tag.id,
+                name: tag.name,
+                color: tag.color,
+                project_count,
+            })
+        })
+        .collect()
+}
+
+pub async fn create_tag(handle: &WorkspaceHandle, payload: TagInput) -> AppResult<Tag> {
+    let (name, color) = validate_tag(payload)?;
+    if find_by_name(&handle.pool, &name).await?.is_some() {
+        return Err(AppError::Validation(format!("tag '{name}' already exists")));
+    }
+    let id = Uuid::new_v4();
+    sqlx::query("INSERT INTO tags (id, name, color, created_at) VALUES (?, ?, ?, ?)")
+        .bind(id.to_string())
+        .bind(&name)
+        .bind(color.as_deref())
+        .bind(Utc::now().to_rfc3339())
+        .execute(&handle.pool)
+        .await?;
+    Ok(Tag { id, name, color })
+}
+
+/// Renames or recolours a tag. Renaming onto another tag's name is refused;
+/// use [`merge_tags`] to combine them.
+pub async fn update_tag(
+    handle: &WorkspaceHandle,
+    tag_id: Uuid,
+    payload: TagInput,
+) -> AppResult<Tag> {
+    let (name, color) = validate_tag(payload)?;
+    if let Some(existing) = find_by_name(&handle.pool, &name).await? {
+        if existing.id != tag_id {
+            return Err(AppError::Validation(format!(
+                "tag '{name}' already exists, merge the tags instead"
+            )));
+        }
+    }
+    let affected = sqlx::query("UPDATE tags SET name = ?, color = ? WHERE id = ?")
+        .bind(&name)
+        .bind(color.as_deref())
+        .bind(tag_id.to_string())
+        .execute(&handle.pool)
+        .await?
+        .rows_affected();
+    if affected == 0 {
+        return Err(tag_not_found(tag_id));
+    }
+    let mut conn = handle.pool.acquire().await?;
+    let tagged = tagged_projects(&mut conn, tag_id).await?;
+    search::index_projects(&mut conn, &tagged).await?;
+    Ok(Tag {
+        id: tag_id,
+        name,
+        color,
+    })
+}
+
+/// Moves every project tagged with one of `source_ids` to `target_id` and
+/// deletes the source tags.
+pub async fn merge_tags(
+    handle: &WorkspaceHandle,
+    source_ids: &[Uuid],
+    target_id: Uuid,
+) -> AppResult<Tag> {
+    let target = get_tag(&handle.pool, target_id).await?;
+    let mut tx = handle.pool.begin().await?;
+    for source_id in source_ids.iter().filter(|id| **id != target_id) {
+        let source_id = source_id.to_string();
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO project_tags (project_id, tag_id)
+            SELECT project_id, ? FROM project_tags WHERE tag_id = ?
+            "#,
+        )
+        .bind(target_id.to_string())
+        .bind(&source_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM project_tags WHERE tag_id = ?")
+            .bind(&source_id)
+            .execute(&mut *tx)
+            .await?;
+        let affected = sqlx::query("DELETE FROM tags WHERE id = ?")
+            .bind(&source_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if affected == 0 {
+            return Err(AppError::Validation(format!("tag not found: {source_id}")));
+        }
+    }
+    let tagged = tagged_projects(&mut tx, target_id).await?;
+    search::index_projects(&mut tx, &tagged).await?;
+    tx.commit().await?;
+    Ok(target)
+}
+
+pub async fn delete_tag(handle: &WorkspaceHandle, tag_id: Uuid) -> AppResult<()> {
+    let mut tx = handle.pool.begin().await?;
+    let tagged = tagged_projects(&mut tx, tag_id).await?;
+    sqlx::query("DELETE FROM project_tags WHERE tag_id = ?")
+        .bind(tag_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+    let affected = sqlx::query("DELETE FROM tags WHERE id = ?")
+        .bind(tag_id.to_string())
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    if affected == 0 {
+        return Err(tag_not_found(tag_id));
+    }
+    search::index_projects(&mut tx, &tagged).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Replaces the tags of a project. Names that match no tag, ignoring case,
+/// create one without a colour.
+pub async fn set_project_tags(
+    handle: &WorkspaceHandle,
+    project_id: Uuid,
+    names: Vec<String>,
+) -> AppResult<Project> {
+    let project_id_str = project_id.to_string();
+    let mut tag_ids = Vec::with_capacity(names.len());
+    for name in names {
+        let (name, _) = validate_tag(TagInput { name, color: None })?;
+        let tag = match find_by_name(&handle.pool, &name).await? {
+            Some(tag) => tag,
+            None => create_tag(handle, TagInput { name, color: None }).await?,
+        };
+        if !tag_ids.contains(&tag.id) {
+            tag_ids.push(tag.id);
+        }
+    }
+
+    let mut tx = handle.pool.begin().await?;
+    let exists: bool = sqlx::query_scalar("SELECT count(*) > 0 FROM projects WHERE id = ?")
+        .bind(&project_id_str)
+        .fetch_one(&mut *tx)
+        .await?;
+    if !exists {
+        return Err(AppError::ProjectNotFound(project_id_str));
+    }
+    sqlx::query("DELETE FROM project_tags WHERE project_id = ?")
+        .bind(&project_id_str)
+        .execute(&mut *tx)
+        .await?;
+    for tag_id in tag_ids {
+        sqlx::query("INSERT INTO project_tags (project_id, tag_id) VALUES (?, ?)")
+            .bind(&project_id_str)
+            .bind(tag_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+    }
+    search::index_project(&mut tx, &project_id_str).await?;
+    tx.commit().await?;
+    project::get_project(handle, project_id).await
+}
+
+async fn tagged_projects(conn: &mut SqliteConnection, tag_id: Uuid) -> AppResult<Vec<String>> {
+    Ok(
+        sqlx::query_scalar("SELECT project_id FROM project_tags WHERE tag_id = ?")
+            .bind(tag_id.to_string())
+            .fetch_all(conn)
+            .await?,
+    )
+}
+
+async fn get_tag(pool: &SqlitePool, tag_id: Uuid) -> AppResult<Tag> {
+    let row = sqlx::query_as::<_, TagRow>("SELECT id, name, color FROM tags WHERE id = ?")
+        .bind(tag_id.to_string())
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| tag_not_found(tag_id))?;
+    row_to_tag(row)
+}
+
+async fn find_by_name(pool: &SqlitePool, name: &str) -> AppResult<Option<Tag>> {
+    sqlx::query_as::<_, TagRow>("SELECT id, name, color FROM tags WHERE name = ?")
+        .bind(name)
+        .fetch_optional(pool)
+        .await?
+        .map(row_to_tag)
+        .transpose()
+}
+
+fn validate_tag(payload: TagInput) -> AppResult<(String, Option<String>)> {
+    let name = payload.name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::Validation("tag name cannot be empty".into()));
+    }
+    let color = payload
+        .color
+        .map(|color| color.trim().to_lowercase())
+        .filter(|color| !color.is_empty());
+    if let Some(color) = &color {
+        let is_hex = color.len() == 7
+            && color.starts_with('#')
+            && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+        if !is_hex {
+            return Err(AppError::Validation(format!(
+                "tag colour must look like #rrggbb: {color}"
+            )));
+        }
+    }
+    Ok((name, color))
+}
+
+fn row_to_tag(row: TagRow) -> AppResult<Tag> {
+    let id = Uuid::parse_str(&row.id)
+        .map_err(|err| AppError::Validation(format!("invalid tag id '{}': {err}", row.id)))?;
+    Ok(Tag {
+        id,
+        name: row.name,
+        color: row.color,
+    })
+}
+
+fn tag_not_found(tag_id: Uuid) -> AppError {
+    AppError::Validation(format!("tag not found: {tag_id}"))
+}
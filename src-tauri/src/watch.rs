@@ -0,0 +1,185 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    error::{AppError, AppResult},
+    models::{
+        Project, ProjectImport, ProjectSuggestion, ProjectSuggestionRow, ScanCandidate, WatchMode,
+        WatchedFolder, WatchedFolderEvent,
+    },
+    project::{self, parse_timestamp},
+    state::WorkspaceHandle,
+};
+
+pub const WATCH_EVENT: &str = "watched-folder-changes";
+
+const SUGGESTION_SELECT: &str = r#"
+SELECT id, watch_id, path, name, markers, detected_at
+FROM project_suggestions
+"#;
+
+/// Adds or suggests the candidates found in `folder`, depending on its mode.
+/// Paths that are already projects, suggested or dismissed are skipped.
+pub async fn apply_candidates(
+    handle: &WorkspaceHandle,
+    folder: &WatchedFolder,
+    candidates: Vec<ScanCandidate>,
+) -> AppResult<WatchedFolderEvent> {
+    let known = project::project_paths(handle).await?;
+    let candidates: Vec<ScanCandidate> = candidates
+        .into_iter()
+        .filter(|candidate| candidate.path != folder.path)
+        .filter(|candidate| !known.contains_key(&project::path_key(&candidate.path)))
+        .collect();
+
+    let mut event = WatchedFolderEvent {
+        workspace_id: handle.meta.id,
+        added: Vec::new(),
+        suggested: Vec::new(),
+    };
+    match folder.mode {
+        WatchMode::AutoAdd => {
+            let imports = candidates
+                .into_iter()
+                .map(|candidate| ProjectImport {
+                    path: candidate.path,
+                    name: Some(candidate.name),
+                })
+                .collect();
+            event.added = project::import_projects(handle, imports).await?;
+        }
+        WatchMode::Suggest => {
+            for candidate in candidates {
+                if let Some(suggestion) = suggest_project(handle, folder.id, candidate).await? {
+                    event.suggested.push(suggestion);
+                }
+            }
+        }
+    }
+    Ok(event)
+}
+
+/// Queues a suggestion, unless the path was already suggested or dismissed.
+async fn suggest_project(
+    handle: &WorkspaceHandle,
+    watch_id: Uuid,
+    candidate: ScanCandidate,
+) -> AppResult<Option<ProjectSuggestion>> {
+    let suggestion = ProjectSuggestion {
+        id: Uuid::new_v4(),
+        watch_id,
+        path: candidate.path,
+        name: candidate.name,
+        markers: candidate.markers,
+        detected_at: Utc::now(),
+    };
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO project_suggestions (id, watch_id, path, name, markers, detected_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT(path) DO NOTHING
+        "#,
+    )
+    .bind(suggestion.id.to_string())
+    .bind(watch_id.to_string())
+    .bind(suggestion.path.to_string_lossy().to_string())
+    .bind(&suggestion.name)
+    .bind(serde_json::to_string(&suggestion.markers)?)
+    .bind(suggestion.detected_at.to_rfc3339())
+    .execute(&handle.pool)
+    .await?
+    .rows_affected();
+    Ok((inserted > 0).then_some(suggestion))
+}
+
+pub async fn list_suggestions(handle: &WorkspaceHandle) -> AppResult<Vec<ProjectSuggestion>> {
+    let rows = sqlx::query_as::<_, ProjectSuggestionRow>(&format!(
+        "{SUGGESTION_SELECT} WHERE dismissed = 0 ORDER BY detected_at DESC"
+    ))
+    .fetch_all(&handle.pool)
+    .await?;
+    let suggestions = rows
+        .into_iter()
+        .map(row_to_suggestion)
+        .collect::<AppResult<Vec<_>>>()?;
+    // Folders removed while the workspace was unavailable leave theirs behind
+    let watched = &handle.meta.watched_folders;
+    Ok(suggestions
+        .into_iter()
+        .filter(|suggestion| {
+            watched
+                .iter()
+                .any(|folder| folder.id == suggestion.watch_id)
+        })
+        .collect())
+}
+
+/// Imports the given suggestions as projects and removes them from the queue.
+pub async fn accept_suggestions(
+    handle: &WorkspaceHandle,
+    suggestion_ids: &[Uuid],
+) -> AppResult<Vec<Project>> {
+    let mut imports = Vec::with_capacity(suggestion_ids.len());
+    for id in suggestion_ids {
+        let row = sqlx::query_as::<_, ProjectSuggestionRow>(&format!(
+            "{SUGGESTION_SELECT} WHERE id = ? AND dismissed = 0"
+        ))
+        .bind(id.to_string())
+        .fetch_optional(&handle.pool)
+        .await?
+        .ok_or_else(|| AppError::Validation(format!("project suggestion not found: {id}")))?;
+        imports.push(ProjectImport {
+            path: PathBuf::from(row.path),
+            name: Some(row.name),
+        });
+    }
+
+    let projects = project::import_projects(handle, imports).await?;
+    for id in suggestion_ids {
+        sqlx::query("DELETE FROM project_suggestions WHERE id = ?")
+            .bind(id.to_string())
+            .execute(&handle.pool)
+            .await?;
+    }
+    Ok(projects)
+}
+
+/// Hides suggestions for good; their paths are not suggested again.
+pub async fn dismiss_suggestions(
+    handle: &WorkspaceHandle,
+    suggestion_ids: &[Uuid],
+) -> AppResult<()> {
+    for id in suggestion_ids {
+        sqlx::query("UPDATE project_suggestions SET dismissed = 1 WHERE id = ?")
+            .bind(id.to_string())
+            .execute(&handle.pool)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Drops the pending suggestions of a folder that is no longer watched.
+pub async fn forget_folder(handle: &WorkspaceHandle, watch_id: Uuid) -> AppResult<()> {
+    sqlx::query("DELETE FROM project_suggestions WHERE watch_id = ? AND dismissed = 0")
+        .bind(watch_id.to_string())
+        .execute(&handle.pool)
+        .await?;
+    Ok(())
+}
+
+fn row_to_suggestion(row: ProjectSuggestionRow) -> AppResult<ProjectSuggestion> {
+    let parse_id = |value: &str| {
+        Uuid::parse_str(value)
+            .map_err(|err| AppError::Validation(format!("invalid id '{value}': {err}")))
+    };
+    Ok(ProjectSuggestion {
+        id: parse_id(&row.id)?,
+        watch_id: parse_id(&row.watch_id)?,
+        path: PathBuf::from(row.path),
+        name: row.name,
+        markers: serde_json::from_str(&row.markers)?,
+        detected_at: parse_timestamp(&row.detected_at)?,
+    })
+}